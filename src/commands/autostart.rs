@@ -0,0 +1,151 @@
+use crate::{
+    error,
+    hypr::{
+        autostart::{
+            add_autostart_entry, autostart_entries, command_executables, is_user_entry,
+            remove_autostart_entry, set_autostart_enabled,
+        },
+        config::expand_variables,
+        includes::ConfigSet,
+    },
+    info, success,
+    utils::{helper_functions::find_executable, types::*},
+};
+use colored::Colorize;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+// `autoricer autostart [add <command> | enable|disable|remove <number>]` manages the `exec-once` entries
+pub fn run_autostart(args: &[String], paths: &Paths) -> io::Result<()> {
+    let mut configs: ConfigSet = ConfigSet::load(&paths.hypr_config, &paths.home)?;
+    let entries: Vec<AutostartEntry> = autostart_entries(&configs);
+
+    let (action, rest) = match args.split_first() {
+        Some((action, rest)) => (action.as_str(), rest),
+        None => return list_autostart(&entries, &configs.variables()),
+    };
+
+    if action == "add" {
+        return add_autostart(&mut configs, paths, &rest.join(" "));
+    }
+
+    let Some(entry) = rest
+        .first()
+        .and_then(|number| number.parse::<usize>().ok())
+        .and_then(|number| entries.get(number.wrapping_sub(1)))
+    else {
+        error!(format!(
+            "Usage: autoricer autostart {} <number>, see `autoricer autostart` for the numbers",
+            action
+        ));
+        return Err(io::Error::from(io::ErrorKind::InvalidInput));
+    };
+
+    match action {
+        "enable" | "disable" if entry.enabled == (action == "enable") => {
+            success!(format!("==> {} is already {}d", entry.command, action));
+            return Ok(());
+        }
+        "enable" => set_autostart_enabled(&mut configs, entry, true)?,
+        "disable" => set_autostart_enabled(&mut configs, entry, false)?,
+        "remove" => remove_autostart_entry(&mut configs, entry)?,
+        _ => {
+            error!(format!("Unknown action: {}", action));
+            return Err(io::Error::from(io::ErrorKind::InvalidInput));
+        }
+    }
+
+    configs.save()?;
+
+    println!(
+        "{} {} {}",
+        format!("==> {}d in", action).green(),
+        entry.file.display().to_string().green(),
+        format!("exec-once = {}", entry.command).green().bold()
+    );
+
+    Ok(())
+}
+
+fn list_autostart(
+    entries: &[AutostartEntry],
+    variables: &BTreeMap<String, String>,
+) -> io::Result<()> {
+    info!("Programs started with Hyprland:");
+
+    for (number, entry) in entries.iter().enumerate() {
+        let status = match entry.enabled {
+            true => "enabled ".green().bold(),
+            false => "disabled".bright_black().bold(),
+        };
+        let origin: &str = match is_user_entry(entry) {
+            true => "user    ",
+            false => "dotfiles",
+        };
+
+        println!(
+            "{:>3}  {}  {}  {}",
+            number + 1,
+            status,
+            origin.bright_black(),
+            entry.command
+        );
+
+        warn_missing_executables(&entry.command, variables);
+    }
+
+    Ok(())
+}
+
+// Adds an entry to ~/.config/hypr/user-autostart.conf, creating and sourcing the file on first use
+fn add_autostart(configs: &mut ConfigSet, paths: &Paths, command: &str) -> io::Result<()> {
+    if command.trim().is_empty() {
+        error!("Usage: autoricer autostart add <command>");
+        return Err(io::Error::from(io::ErrorKind::InvalidInput));
+    }
+
+    let hypr_dir: &Path = paths.hypr_config.parent().unwrap_or(&paths.config);
+    let user_autostart: PathBuf = hypr_dir.join("user-autostart.conf");
+
+    if !user_autostart.exists() {
+        fs::write(
+            &user_autostart,
+            "# Programs added with `autoricer autostart add`\n",
+        )?;
+    }
+
+    if !configs.paths().any(|path| path == user_autostart) {
+        configs.push("source", &user_autostart.display().to_string());
+        configs.save()?;
+
+        *configs = ConfigSet::load(&paths.hypr_config, &paths.home)?;
+    }
+
+    add_autostart_entry(configs, &user_autostart, command)?;
+    configs.save()?;
+
+    println!(
+        "{} {} {}",
+        "==> Added to".green(),
+        user_autostart.display().to_string().green(),
+        format!("exec-once = {}", command).green().bold()
+    );
+
+    warn_missing_executables(command, &configs.variables());
+
+    Ok(())
+}
+
+fn warn_missing_executables(command: &str, variables: &BTreeMap<String, String>) {
+    for executable in command_executables(&expand_variables(command, variables)) {
+        if find_executable(&executable).is_none() {
+            println!(
+                "{}",
+                format!("     {} is not installed", executable).yellow()
+            );
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use crate::{
+    hypr::{
+        autostart::command_executables,
+        config::{expand_variables, HyprlandConfig},
+        includes::ConfigSet,
+    },
+    info, success,
+    utils::{contents::get_yazi_packages, helper_functions::*, types::*},
+    warning,
+};
+use colored::Colorize;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+// Runs all health checks on an existing installation and prints them as a table
+pub fn run_doctor(paths: &Paths) -> io::Result<()> {
+    let mut checks: Vec<Check> = Vec::new();
+
+    info!("Checking your installation...");
+
+    let configs: io::Result<ConfigSet> = ConfigSet::load(&paths.hypr_config, &paths.home);
+
+    check_hypr_config(&configs, &mut checks);
+    check_wallpapers(paths, &mut checks);
+    check_exec_once(&configs, &mut checks);
+    check_zsh_plugins(paths, &mut checks);
+    check_yazi_plugins(paths, &mut checks);
+    check_fonts(paths, &mut checks);
+
+    print_checks(&checks);
+
+    let failed: usize = checks
+        .iter()
+        .filter(|check| matches!(check.status, CheckStatus::Fail))
+        .count();
+
+    if failed > 0 {
+        return Err(io::Error::other(format!("{} check(s) failed", failed)));
+    }
+
+    success!("==> No problems found");
+
+    Ok(())
+}
+
+fn print_checks(checks: &[Check]) {
+    let width: usize = checks
+        .iter()
+        .map(|check| check.name.len())
+        .max()
+        .unwrap_or(0);
+
+    println!();
+
+    for check in checks {
+        let status = match check.status {
+            CheckStatus::Pass => "PASS".green().bold(),
+            CheckStatus::Warn => "WARN".yellow().bold(),
+            CheckStatus::Fail => "FAIL".red().bold(),
+        };
+
+        println!(
+            "{}  {:<width$}  {}",
+            status,
+            check.name,
+            check.details.bright_black(),
+            width = width
+        );
+    }
+}
+
+fn push_check(checks: &mut Vec<Check>, status: CheckStatus, name: &str, details: String) {
+    checks.push(Check {
+        name: name.to_string(),
+        status,
+        details,
+    });
+}
+
+fn check_hypr_config(configs: &io::Result<ConfigSet>, checks: &mut Vec<Check>) {
+    let configs: &ConfigSet = match configs {
+        Ok(configs) => configs,
+        Err(error) => {
+            push_check(
+                checks,
+                CheckStatus::Fail,
+                "hyprland.conf",
+                error.to_string(),
+            );
+            return;
+        }
+    };
+
+    for path in configs.paths() {
+        push_check(
+            checks,
+            CheckStatus::Pass,
+            &path.display().to_string(),
+            "config parses".to_string(),
+        );
+    }
+
+    for path in &configs.missing {
+        push_check(
+            checks,
+            CheckStatus::Fail,
+            &path.display().to_string(),
+            "sourced file does not exist".to_string(),
+        );
+    }
+}
+
+fn check_wallpapers(paths: &Paths, checks: &mut Vec<Check>) {
+    let hyprpaper_config: PathBuf = paths.config.join("hypr/hyprpaper.conf");
+
+    let Ok(config) = HyprlandConfig::load(&hyprpaper_config) else {
+        push_check(
+            checks,
+            CheckStatus::Warn,
+            "hyprpaper.conf",
+            "not found or invalid".to_string(),
+        );
+        return;
+    };
+
+    // `wallpaper = <monitor>,<path>` while `preload = <path>`
+    let wallpapers = config.get_all("preload").into_iter().chain(
+        config
+            .get_all("wallpaper")
+            .into_iter()
+            .filter_map(|value| value.split_once(',').map(|(_, path)| path.to_string())),
+    );
+
+    for wallpaper in wallpapers {
+        let path: PathBuf = expand_home(wallpaper.trim(), &paths.home);
+
+        if path.exists() {
+            push_check(
+                checks,
+                CheckStatus::Pass,
+                "wallpaper",
+                path.display().to_string(),
+            );
+        } else {
+            push_check(
+                checks,
+                CheckStatus::Fail,
+                "wallpaper",
+                format!("{} does not exist", path.display()),
+            );
+        }
+    }
+}
+
+fn check_exec_once(configs: &io::Result<ConfigSet>, checks: &mut Vec<Check>) {
+    let Ok(configs) = configs else {
+        return;
+    };
+
+    let variables: BTreeMap<String, String> = configs.variables();
+
+    for command in configs.get_all("exec-once") {
+        // Hyprland expands variables like `$terminal` before running the command
+        let command: String = expand_variables(&command, &variables);
+
+        for executable in command_executables(&command) {
+            match find_executable(&executable) {
+                Some(path) => push_check(
+                    checks,
+                    CheckStatus::Pass,
+                    "exec-once",
+                    format!("{} -> {}", executable, path.display()),
+                ),
+                None => push_check(
+                    checks,
+                    CheckStatus::Fail,
+                    "exec-once",
+                    format!("{} is not in $PATH", executable),
+                ),
+            }
+        }
+    }
+}
+
+fn check_zsh_plugins(paths: &Paths, checks: &mut Vec<Check>) {
+    let Ok(zshrc) = fs::read_to_string(paths.home.join(".zshrc")) else {
+        push_check(
+            checks,
+            CheckStatus::Warn,
+            "~/.zshrc",
+            "not found".to_string(),
+        );
+        return;
+    };
+
+    for line in zshrc.lines().map(str::trim) {
+        let Some(plugin) = line.strip_prefix("source ") else {
+            continue;
+        };
+
+        let plugin: PathBuf = expand_home(plugin.trim().trim_matches('"'), &paths.home);
+
+        if !plugin.starts_with(paths.home.join(".zsh")) {
+            continue;
+        }
+
+        if plugin.exists() {
+            push_check(
+                checks,
+                CheckStatus::Pass,
+                "zsh plugin",
+                plugin.display().to_string(),
+            );
+        } else {
+            push_check(
+                checks,
+                CheckStatus::Fail,
+                "zsh plugin",
+                format!("{} does not exist", plugin.display()),
+            );
+        }
+    }
+}
+
+fn check_yazi_plugins(paths: &Paths, checks: &mut Vec<Check>) {
+    for package in get_yazi_packages() {
+        let path: PathBuf = yazi_package_path(&paths.config, package);
+
+        if path.exists() {
+            push_check(
+                checks,
+                CheckStatus::Pass,
+                "yazi package",
+                package.to_string(),
+            );
+        } else {
+            push_check(
+                checks,
+                CheckStatus::Warn,
+                "yazi package",
+                format!("{} is not installed", package),
+            );
+        }
+    }
+}
+
+// `owner/repo` installs into plugins/repo.yazi while `owner/repo:name` installs into <repo>/name.yazi
+fn yazi_package_path(config_path: &Path, package: &str) -> PathBuf {
+    let yazi_path: PathBuf = config_path.join("yazi");
+    let (_, repo) = package.split_once('/').unwrap_or(("", package));
+
+    match repo.split_once(':') {
+        Some(("flavors", name)) => yazi_path.join("flavors").join(format!("{}.yazi", name)),
+        Some((_, name)) => yazi_path.join("plugins").join(format!("{}.yazi", name)),
+        None => yazi_path.join("plugins").join(format!("{}.yazi", repo)),
+    }
+}
+
+fn check_fonts(paths: &Paths, checks: &mut Vec<Check>) {
+    let mut fonts: Vec<String> = Vec::new();
+
+    if let Ok(kitty) = fs::read_to_string(paths.config.join("kitty/kitty.conf")) {
+        fonts.extend(
+            kitty
+                .lines()
+                .filter_map(|line| line.trim().strip_prefix("font_family"))
+                .map(|font| font.trim().to_string()),
+        );
+    }
+
+    if let Ok(waybar) = fs::read_to_string(paths.config.join("waybar/style.css")) {
+        fonts.extend(
+            waybar
+                .lines()
+                .filter_map(|line| line.trim().strip_prefix("font-family:"))
+                .flat_map(|families| families.trim_end_matches(';').split(','))
+                .map(|font| font.trim().trim_matches(['"', '\'']).to_string())
+                .filter(|font| !matches!(font.as_str(), "monospace" | "sans-serif" | "serif")),
+        );
+    }
+
+    fonts.sort();
+    fonts.dedup();
+
+    if fonts.is_empty() {
+        return;
+    }
+
+    let Some(installed) = installed_font_families() else {
+        warning!("==> Could not run fc-list. Skipping font checks");
+        return;
+    };
+
+    for font in fonts {
+        if installed
+            .iter()
+            .any(|family| family.eq_ignore_ascii_case(&font))
+        {
+            push_check(checks, CheckStatus::Pass, "font", font);
+        } else {
+            push_check(
+                checks,
+                CheckStatus::Fail,
+                "font",
+                format!("{} is not installed", font),
+            );
+        }
+    }
+}
+
+fn installed_font_families() -> Option<Vec<String>> {
+    let fc_list: PathBuf = find_executable("fc-list")?;
+    let output: Output = Command::new(fc_list).arg(":").arg("family").output().ok()?;
+
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .flat_map(|line| line.split(','))
+            .map(|family| family.trim().to_string())
+            .collect(),
+    )
+}
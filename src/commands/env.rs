@@ -0,0 +1,88 @@
+use crate::{
+    error,
+    hypr::{
+        env::{disable_env_group, enable_env_group, env_group_status},
+        includes::ConfigSet,
+    },
+    info, success,
+    utils::{contents::get_env_groups, types::*},
+};
+use colored::Colorize;
+use std::{io, path::PathBuf};
+
+// `autoricer env [enable|disable <group>...]` manages groups of environment variables in the Hyprland config
+pub fn run_env(args: &[String], paths: &Paths) -> io::Result<()> {
+    let mut configs: ConfigSet = ConfigSet::load(&paths.hypr_config, &paths.home)?;
+    let groups: Vec<EnvGroup> = get_env_groups();
+
+    let (action, names) = match args.split_first() {
+        Some((action, names)) => (action.as_str(), names),
+        None => return list_env_groups(&configs, &groups),
+    };
+
+    if names.is_empty() {
+        error!(format!("Usage: autoricer env {} <group>...", action));
+        return Err(io::Error::from(io::ErrorKind::InvalidInput));
+    }
+
+    for name in names {
+        let Some(group) = groups.iter().find(|group| group.name == name) else {
+            error!(format!("Unknown group: {}", name));
+            return Err(io::Error::from(io::ErrorKind::InvalidInput));
+        };
+
+        let changes: Vec<(PathBuf, String)> = match action {
+            "enable" => enable_env_group(&mut configs, group, &[])?,
+            "disable" => disable_env_group(&mut configs, group)?,
+            _ => {
+                error!(format!("Unknown action: {}", action));
+                return Err(io::Error::from(io::ErrorKind::InvalidInput));
+            }
+        };
+
+        if changes.is_empty() {
+            success!(format!("==> {} is already {}d", group.name, action));
+        }
+
+        for (file, assignment) in changes {
+            println!(
+                "{} {} {}",
+                format!("==> {}d in", action).green(),
+                file.display().to_string().green(),
+                format!("env = {}", assignment).green().bold()
+            );
+        }
+    }
+
+    configs.save()?;
+
+    Ok(())
+}
+
+fn list_env_groups(configs: &ConfigSet, groups: &[EnvGroup]) -> io::Result<()> {
+    info!("Environment variable groups:");
+
+    for group in groups {
+        let status = match env_group_status(configs, group) {
+            EnvGroupStatus::Enabled => "enabled ".green().bold(),
+            EnvGroupStatus::Partial => "partial ".yellow().bold(),
+            EnvGroupStatus::Disabled => "disabled".bright_black().bold(),
+        };
+
+        println!(
+            "{}  {:<12} {}",
+            status,
+            group.name,
+            group.description.bright_black()
+        );
+
+        for (name, value) in &group.vars {
+            println!(
+                "{}",
+                format!("            {}={}", name, value).bright_black()
+            );
+        }
+    }
+
+    Ok(())
+}
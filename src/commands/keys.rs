@@ -0,0 +1,168 @@
+use crate::{
+    error,
+    hypr::{binds::parse_binds, includes::ConfigSet},
+    info,
+    utils::{contents::get_bind_groups, types::*},
+    warning,
+};
+use colored::Colorize;
+use std::io;
+
+// `autoricer keys [--markdown|--html]` prints a cheatsheet of all keybindings in the Hyprland config
+pub fn run_keys(args: &[String], paths: &Paths) -> io::Result<()> {
+    let format: CheatsheetFormat = match args.first().map(String::as_str) {
+        None => CheatsheetFormat::Terminal,
+        Some("--markdown" | "--md") => CheatsheetFormat::Markdown,
+        Some("--html") => CheatsheetFormat::Html,
+        Some(_) => {
+            error!("Usage: autoricer keys [--markdown|--html]");
+            return Err(io::Error::from(io::ErrorKind::InvalidInput));
+        }
+    };
+
+    let configs: ConfigSet = ConfigSet::load(&paths.hypr_config, &paths.home)?;
+    let binds: Vec<Bind> = parse_binds(&configs);
+
+    if binds.is_empty() {
+        warning!(format!(
+            "==> No keybindings found in {}",
+            paths.hypr_config.display()
+        ));
+        return Ok(());
+    }
+
+    let groups: Vec<(String, Vec<&Bind>)> = group_binds(&binds);
+
+    match format {
+        CheatsheetFormat::Terminal => print_cheatsheet(&groups),
+        CheatsheetFormat::Markdown => print!("{}", render_markdown(&groups)),
+        CheatsheetFormat::Html => print!("{}", render_html(&groups)),
+    }
+
+    Ok(())
+}
+
+// Sorts binds into the cheatsheet sections, keeping the order of the config inside each section
+fn group_binds(binds: &[Bind]) -> Vec<(String, Vec<&Bind>)> {
+    let mut groups: Vec<(String, Vec<&Bind>)> = get_bind_groups()
+        .iter()
+        .map(|(name, _)| (name.to_string(), Vec::new()))
+        .chain([
+            (String::from("Mouse"), Vec::new()),
+            (String::from("Other"), Vec::new()),
+        ])
+        .collect();
+
+    for bind in binds {
+        let name: String = match &bind.submap {
+            Some(submap) => format!("Submap: {}", submap),
+            None if bind.flags.contains('m') => String::from("Mouse"),
+            None => get_bind_groups()
+                .into_iter()
+                .find(|(_, dispatchers)| dispatchers.contains(&bind.dispatcher.as_str()))
+                .map_or(String::from("Other"), |(name, _)| name.to_string()),
+        };
+
+        match groups.iter_mut().find(|(group, _)| *group == name) {
+            Some((_, binds)) => binds.push(bind),
+            None => groups.push((name, vec![bind])),
+        }
+    }
+
+    groups.retain(|(_, binds)| !binds.is_empty());
+
+    groups
+}
+
+fn print_cheatsheet(groups: &[(String, Vec<&Bind>)]) {
+    let width: usize = groups
+        .iter()
+        .flat_map(|(_, binds)| binds.iter().map(|bind| bind.combo().len()))
+        .max()
+        .unwrap_or(0);
+
+    for (name, binds) in groups {
+        info!(name);
+
+        for bind in binds {
+            println!(
+                "  {:<width$}  {}",
+                bind.combo().green().bold(),
+                bind.action(),
+                width = width
+            );
+        }
+    }
+}
+
+fn render_markdown(groups: &[(String, Vec<&Bind>)]) -> String {
+    let mut markdown: String = String::from("# Keybindings\n");
+
+    for (name, binds) in groups {
+        markdown.push_str(&format!(
+            "\n## {}\n\n| Keys | Action |\n| --- | --- |\n",
+            name
+        ));
+
+        for bind in binds {
+            let keys: Vec<String> = bind
+                .keys()
+                .into_iter()
+                .map(|key| format!("<kbd>{}</kbd>", escape_markdown(key)))
+                .collect();
+
+            markdown.push_str(&format!(
+                "| {} | {} |\n",
+                keys.join(" + "),
+                escape_markdown(&bind.action())
+            ));
+        }
+    }
+
+    markdown
+}
+
+fn render_html(groups: &[(String, Vec<&Bind>)]) -> String {
+    let mut html: String = String::from(concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Keybindings</title>\n",
+        "<style>body { font-family: sans-serif; background: #2d353b; color: #d3c6aa; } ",
+        "td { padding: 0.2em 1em; } kbd { background: #475258; border-radius: 3px; padding: 0 0.3em; }</style>\n",
+        "</head>\n<body>\n<h1>Keybindings</h1>\n"
+    ));
+
+    for (name, binds) in groups {
+        html.push_str(&format!("<h2>{}</h2>\n<table>\n", escape_html(name)));
+
+        for bind in binds {
+            let keys: Vec<String> = bind
+                .keys()
+                .into_iter()
+                .map(|key| format!("<kbd>{}</kbd>", escape_html(key)))
+                .collect();
+
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td></tr>\n",
+                keys.join(" + "),
+                escape_html(&bind.action())
+            ));
+        }
+
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+
+    html
+}
+
+// Markdown renders inline HTML, so it's escaped as well
+fn escape_markdown(text: &str) -> String {
+    escape_html(text).replace('|', "\\|")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
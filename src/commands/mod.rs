@@ -0,0 +1,24 @@
+use crate::{error, Paths};
+use colored::Colorize;
+use std::io;
+
+pub mod autostart;
+pub mod doctor;
+pub mod env;
+pub mod keys;
+pub mod wallpaper;
+
+// Runs a subcommand like `autoricer doctor` instead of the installer
+pub fn run_command(command: &str, args: &[String], paths: &Paths) -> io::Result<()> {
+    match command {
+        "autostart" => autostart::run_autostart(args, paths),
+        "doctor" => doctor::run_doctor(paths),
+        "env" => env::run_env(args, paths),
+        "keys" => keys::run_keys(args, paths),
+        "wallpaper" => wallpaper::run_wallpaper(args, paths),
+        _ => {
+            error!(format!("Unknown command: {}", command));
+            Err(io::Error::from(io::ErrorKind::InvalidInput))
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{
+    error,
+    hypr::{
+        config::HyprlandConfig,
+        ipc::{hyprpaper_socket, send_checked},
+        wallpaper::set_wallpaper,
+    },
+    success,
+    utils::types::*,
+    warning,
+};
+use colored::Colorize;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+// `autoricer wallpaper set <image> [--monitor NAME]` copies an image into the wallpaper directory and sets it in hyprpaper.conf
+pub fn run_wallpaper(args: &[String], paths: &Paths) -> io::Result<()> {
+    let (Some("set"), Some(image)) = (args.first().map(String::as_str), args.get(1)) else {
+        return usage();
+    };
+
+    let monitor: Option<&str> = match args[2..] {
+        [] => None,
+        [ref flag, ref name] if flag == "--monitor" => Some(name.as_str()),
+        _ => return usage(),
+    };
+
+    let image: &Path = Path::new(image);
+
+    if !image.is_file() {
+        error!(format!("{} is not a file", image.display()));
+        return Err(io::Error::from(io::ErrorKind::NotFound));
+    }
+
+    let Some(file_name) = image.file_name() else {
+        return usage();
+    };
+
+    let wallpaper: PathBuf = paths.wallpapers.join(file_name);
+
+    fs::create_dir_all(&paths.wallpapers)?;
+
+    // The image may already be inside the wallpaper directory
+    if fs::canonicalize(image)? != fs::canonicalize(&paths.wallpapers)?.join(file_name) {
+        fs::copy(image, &wallpaper)?;
+        success!(format!("==> Copied wallpaper to {}", wallpaper.display()));
+    }
+
+    // hyprpaper expands `~` in its config, which keeps it readable
+    let image_path: PathBuf = wallpaper.clone();
+    let wallpaper: String = match wallpaper.strip_prefix(&paths.home) {
+        Ok(relative) => format!("~/{}", relative.display()),
+        Err(_) => wallpaper.display().to_string(),
+    };
+
+    let hyprpaper_config: PathBuf = paths.hypr_config.with_file_name("hyprpaper.conf");
+    let mut config: HyprlandConfig = match hyprpaper_config.exists() {
+        true => HyprlandConfig::load(&hyprpaper_config)?,
+        false => HyprlandConfig::parse("")?,
+    };
+
+    set_wallpaper(&mut config, &wallpaper, monitor)?;
+    config.save(&hyprpaper_config)?;
+
+    println!(
+        "{} {} {}",
+        "==> Set wallpaper in".green(),
+        hyprpaper_config.display().to_string().green(),
+        format!("{},{}", monitor.unwrap_or_default(), wallpaper)
+            .green()
+            .bold()
+    );
+
+    let Some(socket) = hyprpaper_socket() else {
+        return Ok(());
+    };
+
+    let applied: io::Result<()> = [
+        format!("preload {}", image_path.display()),
+        format!(
+            "wallpaper {},{}",
+            monitor.unwrap_or_default(),
+            image_path.display()
+        ),
+        String::from("unload unused"),
+    ]
+    .iter()
+    .try_for_each(|command| send_checked(&socket, command));
+
+    match applied {
+        Ok(()) => success!("==> Applied the wallpaper through hyprpaper"),
+        Err(error) => warning!(format!(
+            "==> Could not apply the wallpaper live, it will be used after restarting hyprpaper: {}",
+            error
+        )),
+    }
+
+    Ok(())
+}
+
+fn usage() -> io::Result<()> {
+    error!("Usage: autoricer wallpaper set <image> [--monitor NAME]");
+    Err(io::Error::from(io::ErrorKind::InvalidInput))
+}
@@ -0,0 +1,163 @@
+use crate::{DrmCard, GpuVendor};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+// Lists all DRM cards with the card driving the boot display first and NVIDIA cards last
+pub fn detect_drm_cards(sys_path: &Path) -> Vec<DrmCard> {
+    let Ok(entries) = fs::read_dir(sys_path.join("class/drm")) else {
+        return Vec::new();
+    };
+
+    let mut cards: Vec<DrmCard> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name: String = entry.file_name().to_string_lossy().to_string();
+
+            // Connectors like `card0-eDP-1` live next to the cards
+            if !name
+                .strip_prefix("card")?
+                .chars()
+                .all(|c| c.is_ascii_digit())
+            {
+                return None;
+            }
+
+            let device: PathBuf = entry.path().join("device");
+            let pci_address: String = fs::read_to_string(device.join("uevent"))
+                .ok()?
+                .lines()
+                .find_map(|line| line.strip_prefix("PCI_SLOT_NAME="))?
+                .trim()
+                .to_string();
+
+            let vendor: String = fs::read_to_string(device.join("vendor")).ok()?;
+            let boot_vga: bool = fs::read_to_string(device.join("boot_vga"))
+                .is_ok_and(|boot_vga| boot_vga.trim() == "1");
+
+            Some(DrmCard {
+                by_path: PathBuf::from(format!("/dev/dri/by-path/pci-{}-card", pci_address)),
+                name,
+                pci_address,
+                vendor: GpuVendor::from_pci_id(vendor.trim()),
+                boot_vga,
+            })
+        })
+        .collect();
+
+    // `card2` comes before `card10`
+    cards.sort_by_key(|card| {
+        (
+            !card.boot_vga,
+            matches!(card.vendor, GpuVendor::Nvidia),
+            card_number(&card.name),
+        )
+    });
+
+    cards
+}
+
+fn card_number(name: &str) -> u32 {
+    name.trim_start_matches("card").parse().unwrap_or(u32::MAX)
+}
+
+// Laptops with an integrated GPU render on it and only offload to the NVIDIA card
+pub fn is_hybrid(cards: &[DrmCard]) -> bool {
+    cards.len() > 1
+        && cards
+            .first()
+            .is_some_and(|card| !matches!(card.vendor, GpuVendor::Nvidia))
+        && cards
+            .iter()
+            .any(|card| matches!(card.vendor, GpuVendor::Nvidia))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, os::unix::fs::symlink, process};
+
+    // Creates `class/drm/<card>` with its PCI device and a connector next to it
+    fn add_card(sys: &Path, card: &str, pci_address: &str, vendor: &str, boot_vga: bool) {
+        let device: PathBuf = sys.join("devices").join(pci_address);
+        let drm: PathBuf = sys.join("class/drm");
+
+        fs::create_dir_all(&device).unwrap();
+        fs::create_dir_all(drm.join(card)).unwrap();
+        fs::create_dir_all(drm.join(format!("{}-DP-1", card))).unwrap();
+
+        fs::write(
+            device.join("uevent"),
+            format!("DRIVER=test\nPCI_SLOT_NAME={}\n", pci_address),
+        )
+        .unwrap();
+        fs::write(device.join("vendor"), format!("{}\n", vendor)).unwrap();
+        fs::write(
+            device.join("boot_vga"),
+            if boot_vga { "1\n" } else { "0\n" },
+        )
+        .unwrap();
+
+        symlink(&device, drm.join(card).join("device")).unwrap();
+    }
+
+    fn names(cards: &[DrmCard]) -> Vec<&str> {
+        cards.iter().map(|card| card.name.as_str()).collect()
+    }
+
+    #[test]
+    fn puts_the_boot_display_first_and_skips_connectors() {
+        let sys: PathBuf = env::temp_dir().join(format!("autoricer-drm-{}", process::id()));
+
+        add_card(&sys, "card0", "0000:01:00.0", "0x10de", false);
+        add_card(&sys, "card1", "0000:00:02.0", "0x8086", true);
+        fs::create_dir_all(sys.join("class/drm/card1-eDP-1")).unwrap();
+        fs::create_dir_all(sys.join("class/drm/renderD128")).unwrap();
+
+        let cards: Vec<DrmCard> = detect_drm_cards(&sys);
+
+        assert_eq!(names(&cards), ["card1", "card0"]);
+        assert_eq!(cards[0].pci_address, "0000:00:02.0");
+        assert_eq!(
+            cards[0].by_path,
+            PathBuf::from("/dev/dri/by-path/pci-0000:00:02.0-card")
+        );
+        assert!(is_hybrid(&cards));
+
+        fs::remove_dir_all(&sys).unwrap();
+    }
+
+    #[test]
+    fn sorts_cards_by_number_with_nvidia_last() {
+        let sys: PathBuf = env::temp_dir().join(format!("autoricer-drm-order-{}", process::id()));
+
+        add_card(&sys, "card10", "0000:05:00.0", "0x1002", false);
+        add_card(&sys, "card2", "0000:03:00.0", "0x1002", false);
+        add_card(&sys, "card1", "0000:01:00.0", "0x10de", false);
+
+        let cards: Vec<DrmCard> = detect_drm_cards(&sys);
+
+        assert_eq!(names(&cards), ["card2", "card10", "card1"]);
+        assert!(is_hybrid(&cards));
+
+        fs::remove_dir_all(&sys).unwrap();
+    }
+
+    #[test]
+    fn only_nvidia_or_a_single_card_is_not_hybrid() {
+        let sys: PathBuf = env::temp_dir().join(format!("autoricer-drm-single-{}", process::id()));
+
+        add_card(&sys, "card0", "0000:01:00.0", "0x10de", true);
+
+        assert!(!is_hybrid(&detect_drm_cards(&sys)));
+
+        // Desktops with two NVIDIA cards don't offload either
+        add_card(&sys, "card1", "0000:02:00.0", "0x10de", false);
+
+        assert!(!is_hybrid(&detect_drm_cards(&sys)));
+        assert!(detect_drm_cards(&sys.join("missing")).is_empty());
+
+        fs::remove_dir_all(&sys).unwrap();
+    }
+}
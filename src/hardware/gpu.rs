@@ -0,0 +1,202 @@
+use crate::{Gpu, GpuReport, GpuVendor, NvidiaDriver};
+use std::{
+    fs::{self, DirEntry},
+    path::Path,
+};
+
+// PCI display controller classes: VGA compatible, XGA, 3D controller and other
+const DISPLAY_CLASS_PREFIX: &str = "0x03";
+
+// Finds all graphics cards through sysfs and checks which NVIDIA driver is loaded
+pub fn detect_gpus(sys_path: &Path, proc_path: &Path) -> GpuReport {
+    let gpus: Vec<Gpu> = pci_gpus(&sys_path.join("bus/pci/devices"));
+    let modules: Vec<String> = loaded_modules(&proc_path.join("modules"));
+
+    let nvidia_driver: NvidiaDriver =
+        if proc_path.join("driver/nvidia").exists() || modules.iter().any(|m| m == "nvidia") {
+            NvidiaDriver::Proprietary
+        } else if modules.iter().any(|m| m == "nouveau") {
+            NvidiaDriver::Nouveau
+        } else {
+            NvidiaDriver::None
+        };
+
+    let mut reasons: Vec<String> = gpus
+        .iter()
+        .map(|gpu| {
+            format!(
+                "Found {} graphics card at PCI address {}",
+                gpu.vendor, gpu.pci_address
+            )
+        })
+        .collect();
+
+    if gpus.is_empty() {
+        reasons.push("Could not find any graphics card in sysfs".to_string());
+    }
+
+    reasons.push(match nvidia_driver {
+        NvidiaDriver::Proprietary => "The proprietary NVIDIA driver is loaded".to_string(),
+        NvidiaDriver::Nouveau => {
+            "The open source nouveau driver is loaded, which doesn't need the NVIDIA environment variables"
+                .to_string()
+        }
+        NvidiaDriver::None => "No NVIDIA driver is loaded".to_string(),
+    });
+
+    GpuReport {
+        gpus,
+        nvidia_driver,
+        reasons,
+    }
+}
+
+fn pci_gpus(pci_devices: &Path) -> Vec<Gpu> {
+    let Ok(entries) = fs::read_dir(pci_devices) else {
+        return Vec::new();
+    };
+
+    let mut gpus: Vec<Gpu> = entries
+        .flatten()
+        .filter_map(|entry: DirEntry| {
+            let class: String = read_trimmed(&entry.path().join("class"))?;
+
+            if !class.starts_with(DISPLAY_CLASS_PREFIX) {
+                return None;
+            }
+
+            let vendor: String = read_trimmed(&entry.path().join("vendor"))?;
+
+            Some(Gpu {
+                pci_address: entry.file_name().to_string_lossy().to_string(),
+                vendor: GpuVendor::from_pci_id(&vendor),
+            })
+        })
+        .collect();
+
+    gpus.sort_by(|a, b| a.pci_address.cmp(&b.pci_address));
+
+    gpus
+}
+
+// First column of /proc/modules is the module name
+fn loaded_modules(modules: &Path) -> Vec<String> {
+    fs::read_to_string(modules)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(String::from)
+        .collect()
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|contents| contents.trim().to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, path::PathBuf, process};
+
+    // Builds a sysfs and procfs tree with an Intel iGPU, an NVIDIA dGPU and a sound card
+    fn fixture(name: &str, modules: &str) -> PathBuf {
+        let dir: PathBuf =
+            env::temp_dir().join(format!("autoricer-gpu-{}-{}", name, process::id()));
+        let devices: PathBuf = dir.join("sys/bus/pci/devices");
+
+        for (address, class, vendor) in [
+            ("0000:01:00.0", "0x030200", "0x10de"),
+            ("0000:00:02.0", "0x030000", "0x8086"),
+            ("0000:00:1f.3", "0x040380", "0x8086"),
+        ] {
+            fs::create_dir_all(devices.join(address)).unwrap();
+            fs::write(devices.join(address).join("class"), format!("{}\n", class)).unwrap();
+            fs::write(
+                devices.join(address).join("vendor"),
+                format!("{}\n", vendor),
+            )
+            .unwrap();
+        }
+
+        fs::create_dir_all(dir.join("proc")).unwrap();
+        fs::write(dir.join("proc/modules"), modules).unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn finds_display_controllers_ordered_by_address() {
+        let dir: PathBuf = fixture("cards", "");
+        let report: GpuReport = detect_gpus(&dir.join("sys"), &dir.join("proc"));
+
+        let addresses: Vec<&str> = report
+            .gpus
+            .iter()
+            .map(|gpu| gpu.pci_address.as_str())
+            .collect();
+        assert_eq!(addresses, ["0000:00:02.0", "0000:01:00.0"]);
+        assert!(matches!(report.gpus[0].vendor, GpuVendor::Intel));
+        assert!(matches!(report.gpus[1].vendor, GpuVendor::Nvidia));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn detects_the_loaded_nvidia_driver() {
+        let dir: PathBuf = fixture(
+            "proprietary",
+            "nvidia_drm 126976 4 - Live 0x0000000000000000\nnvidia 54632448 40 nvidia_drm, Live 0x0000000000000000\n",
+        );
+        let report: GpuReport = detect_gpus(&dir.join("sys"), &dir.join("proc"));
+
+        assert!(matches!(report.nvidia_driver, NvidiaDriver::Proprietary));
+        assert!(report.needs_nvidia_env_vars());
+        fs::remove_dir_all(&dir).unwrap();
+
+        // The driver can also be built into the kernel and only show up in /proc/driver
+        let dir: PathBuf = fixture("builtin", "");
+        fs::create_dir_all(dir.join("proc/driver/nvidia")).unwrap();
+
+        let report: GpuReport = detect_gpus(&dir.join("sys"), &dir.join("proc"));
+
+        assert!(matches!(report.nvidia_driver, NvidiaDriver::Proprietary));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn nouveau_does_not_need_the_nvidia_env_vars() {
+        let dir: PathBuf = fixture(
+            "nouveau",
+            "nouveau 3088384 1 - Live 0x0000000000000000\ni915 4091904 2 - Live 0x0000000000000000\n",
+        );
+        let report: GpuReport = detect_gpus(&dir.join("sys"), &dir.join("proc"));
+
+        assert!(matches!(report.nvidia_driver, NvidiaDriver::Nouveau));
+        assert!(!report.needs_nvidia_env_vars());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reports_missing_driver_and_missing_cards() {
+        let dir: PathBuf = fixture("none", "i915 4091904 2 - Live 0x0000000000000000\n");
+        let report: GpuReport = detect_gpus(&dir.join("sys"), &dir.join("proc"));
+
+        assert!(matches!(report.nvidia_driver, NvidiaDriver::None));
+        assert_eq!(report.reasons.last().unwrap(), "No NVIDIA driver is loaded");
+
+        // Without sysfs there are no cards, so the NVIDIA variables aren't needed
+        let report: GpuReport = detect_gpus(&dir.join("missing"), &dir.join("proc"));
+
+        assert!(report.gpus.is_empty());
+        assert!(!report.needs_nvidia_env_vars());
+        assert_eq!(
+            report.reasons[0],
+            "Could not find any graphics card in sysfs"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
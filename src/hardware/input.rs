@@ -0,0 +1,53 @@
+use crate::{InputDevice, InputDeviceKind};
+use std::{fs, path::Path};
+
+// Set on devices which move a pointer instead of mapping to the screen like touchscreens do
+const INPUT_PROP_POINTER: u64 = 1 << 0;
+
+// Lists touchpads and mice from /proc/bus/input/devices
+pub fn detect_input_devices(proc_path: &Path) -> Vec<InputDevice> {
+    let Ok(devices) = fs::read_to_string(proc_path.join("bus/input/devices")) else {
+        return Vec::new();
+    };
+
+    devices
+        .split("\n\n")
+        .filter_map(parse_input_device)
+        .collect()
+}
+
+// Every device is a block of lines like `N: Name="..."`, `H: Handlers=mouse0 event4` and `B: PROP=5`
+fn parse_input_device(block: &str) -> Option<InputDevice> {
+    let mut name: String = String::new();
+    let mut handlers: Vec<&str> = Vec::new();
+    let mut properties: u64 = 0;
+    let mut absolute: bool = false;
+
+    for line in block.lines() {
+        if let Some(value) = line.strip_prefix("N: Name=") {
+            name = value.trim_matches('"').to_string();
+        } else if let Some(value) = line.strip_prefix("H: Handlers=") {
+            handlers = value.split_whitespace().collect();
+        } else if let Some(value) = line.strip_prefix("B: PROP=") {
+            properties = u64::from_str_radix(value.trim(), 16).unwrap_or(0);
+        } else if line.starts_with("B: ABS=") {
+            absolute = true;
+        }
+    }
+
+    if !handlers.iter().any(|handler| handler.starts_with("mouse")) {
+        return None;
+    }
+
+    let lowercase: String = name.to_lowercase();
+    let is_touchpad: bool = lowercase.contains("touchpad")
+        || lowercase.contains("trackpad")
+        || (absolute && properties & INPUT_PROP_POINTER != 0);
+
+    let kind: InputDeviceKind = match is_touchpad {
+        true => InputDeviceKind::Touchpad,
+        false => InputDeviceKind::Mouse,
+    };
+
+    Some(InputDevice { name, kind })
+}
@@ -0,0 +1,38 @@
+use crate::LaptopReport;
+use std::{fs, path::Path};
+
+// Looks for batteries under /sys/class/power_supply and backlights under /sys/class/backlight
+pub fn detect_laptop(sys_path: &Path) -> LaptopReport {
+    // Peripherals like wireless mice report a battery too, but with `scope` set to `Device`
+    let batteries: Vec<String> = list_dir(&sys_path.join("class/power_supply"))
+        .into_iter()
+        .filter(|name| name.starts_with("BAT"))
+        .filter(|name| {
+            let supply = sys_path.join("class/power_supply").join(name);
+
+            fs::read_to_string(supply.join("type")).is_ok_and(|kind| kind.trim() == "Battery")
+                && !fs::read_to_string(supply.join("scope"))
+                    .is_ok_and(|scope| scope.trim() == "Device")
+        })
+        .collect();
+
+    LaptopReport {
+        batteries,
+        backlights: list_dir(&sys_path.join("class/backlight")),
+    }
+}
+
+fn list_dir(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+
+    names.sort();
+
+    names
+}
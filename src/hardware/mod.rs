@@ -0,0 +1,5 @@
+pub mod drm;
+pub mod gpu;
+pub mod input;
+pub mod laptop;
+pub mod monitors;
@@ -0,0 +1,174 @@
+use crate::Monitor;
+use std::{fs, path::Path};
+
+const EDID_HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+
+// Scales Hyprland handles well; the resolution has to be divisible by the scale
+const SCALES: [f64; 7] = [1.0, 1.25, 1.5, 1.6, 2.0, 2.5, 3.0];
+
+// DPI at which a scale of 1 looks right
+const BASE_DPI: f64 = 96.0;
+
+// Lists connected outputs like `card1-eDP-1` under /sys/class/drm together with their EDID data
+pub fn detect_monitors(sys_path: &Path) -> Vec<Monitor> {
+    let Ok(entries) = fs::read_dir(sys_path.join("class/drm")) else {
+        return Vec::new();
+    };
+
+    let mut monitors: Vec<Monitor> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name: String = entry.file_name().to_string_lossy().to_string();
+            let (_, connector) = file_name.split_once('-')?;
+
+            let status: String = fs::read_to_string(entry.path().join("status")).ok()?;
+
+            if status.trim() != "connected" {
+                return None;
+            }
+
+            let edid: Vec<u8> = fs::read(entry.path().join("edid")).ok()?;
+
+            parse_edid(connector, &edid)
+        })
+        .collect();
+
+    monitors.sort_by(|a, b| a.name.cmp(&b.name));
+
+    monitors
+}
+
+// Reads the preferred timing, physical size and monitor name from an EDID base block
+pub fn parse_edid(name: &str, edid: &[u8]) -> Option<Monitor> {
+    if edid.len() < 128 || edid[..8] != EDID_HEADER {
+        return None;
+    }
+
+    // Three 5-bit letters packed big endian, e.g. `DEL` or `SAM`
+    let vendor: u16 = u16::from_be_bytes([edid[8], edid[9]]);
+    let manufacturer: String = [10, 5, 0]
+        .iter()
+        .map(|shift| (((vendor >> shift) & 0x1f) as u8 + b'A' - 1) as char)
+        .collect();
+
+    let descriptors: Vec<&[u8]> = (0..4).map(|i| &edid[54 + i * 18..72 + i * 18]).collect();
+
+    // The first detailed timing descriptor is the preferred mode
+    let timing: &[u8] = descriptors
+        .iter()
+        .find(|d| u16::from_le_bytes([d[0], d[1]]) != 0)?;
+
+    let pixel_clock: f64 = u16::from_le_bytes([timing[0], timing[1]]) as f64 * 10_000.0;
+    let width: u32 = timing[2] as u32 | ((timing[4] as u32 & 0xf0) << 4);
+    let h_blank: u32 = timing[3] as u32 | ((timing[4] as u32 & 0x0f) << 8);
+    let height: u32 = timing[5] as u32 | ((timing[7] as u32 & 0xf0) << 4);
+    let v_blank: u32 = timing[6] as u32 | ((timing[7] as u32 & 0x0f) << 8);
+
+    let total: f64 = ((width + h_blank) * (height + v_blank)) as f64;
+    let refresh_rate: f64 = if total > 0.0 {
+        pixel_clock / total
+    } else {
+        60.0
+    };
+
+    // The timing holds the size in mm, older monitors only fill in cm in the basic parameters
+    let mut width_mm: u32 = timing[12] as u32 | ((timing[14] as u32 & 0xf0) << 4);
+    let mut height_mm: u32 = timing[13] as u32 | ((timing[14] as u32 & 0x0f) << 8);
+
+    if width_mm == 0 || height_mm == 0 {
+        width_mm = edid[21] as u32 * 10;
+        height_mm = edid[22] as u32 * 10;
+    }
+
+    // Display descriptor 0xfc holds the monitor name, terminated by a line feed
+    let model: Option<String> = descriptors
+        .iter()
+        .find(|d| d[0] == 0 && d[1] == 0 && d[3] == 0xfc)
+        .map(|d| {
+            String::from_utf8_lossy(&d[5..18])
+                .split('\n')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        })
+        .filter(|model| !model.is_empty());
+
+    Some(Monitor {
+        name: name.to_string(),
+        manufacturer,
+        model,
+        width,
+        height,
+        refresh_rate,
+        width_mm,
+        height_mm,
+    })
+}
+
+// Picks the scale closest to the monitor's DPI which still results in a whole number of logical pixels
+pub fn hidpi_scale(monitor: &Monitor) -> f64 {
+    if monitor.width_mm == 0 {
+        return 1.0;
+    }
+
+    let dpi: f64 = monitor.width as f64 / (monitor.width_mm as f64 / 25.4);
+    let ideal: f64 = dpi / BASE_DPI;
+
+    SCALES
+        .iter()
+        .copied()
+        .filter(|scale| {
+            (monitor.width as f64 / scale).fract() == 0.0
+                && (monitor.height as f64 / scale).fract() == 0.0
+        })
+        .min_by(|a, b| (a - ideal).abs().total_cmp(&(b - ideal).abs()))
+        .unwrap_or(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Base block of a Dell U2720Q: 3840x2160 at 60 Hz, 597x336 mm
+    const EDID: [u8; 128] = [
+        0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x10, 0xac, 0x2e, 0xa1, 0x4c, 0x4a, 0x4b,
+        0x30, 0x1e, 0x1e, 0x01, 0x04, 0xb5, 0x3c, 0x22, 0x78, 0x3a, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x4d, 0xd0, 0x00, 0xa0, 0xf0, 0x70,
+        0x3e, 0x80, 0x30, 0x20, 0x35, 0x00, 0x55, 0x50, 0x21, 0x00, 0x00, 0x1a, 0x00, 0x00, 0x00,
+        0xfc, 0x00, 0x44, 0x45, 0x4c, 0x4c, 0x20, 0x55, 0x32, 0x37, 0x32, 0x30, 0x51, 0x0a, 0x20,
+        0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2c,
+    ];
+
+    #[test]
+    fn parses_edid_base_block() {
+        let monitor: Monitor = parse_edid("DP-1", &EDID).unwrap();
+
+        assert_eq!(monitor.name, "DP-1");
+        assert_eq!(monitor.manufacturer, "DEL");
+        assert_eq!(monitor.model.as_deref(), Some("DELL U2720Q"));
+        assert_eq!((monitor.width, monitor.height), (3840, 2160));
+        assert_eq!(monitor.refresh_rate.round(), 60.0);
+        assert_eq!((monitor.width_mm, monitor.height_mm), (597, 336));
+        assert_eq!(hidpi_scale(&monitor), 1.6);
+    }
+
+    #[test]
+    fn falls_back_to_the_basic_screen_size() {
+        let mut edid: [u8; 128] = EDID;
+        edid[66..69].fill(0);
+
+        let monitor: Monitor = parse_edid("DP-1", &edid).unwrap();
+
+        assert_eq!((monitor.width_mm, monitor.height_mm), (600, 340));
+    }
+
+    #[test]
+    fn rejects_invalid_edid() {
+        assert!(parse_edid("DP-1", &EDID[..127]).is_none());
+        assert!(parse_edid("DP-1", &[0; 128]).is_none());
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{hypr::includes::ConfigSet, AutostartEntry};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::{collections::BTreeMap, io, path::Path};
+
+static DISABLED_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\s*)#+\s*exec-once\s*=\s*(.+?)\s*$").unwrap());
+
+// All enabled and commented out `exec-once` lines, file by file
+pub fn autostart_entries(configs: &ConfigSet) -> Vec<AutostartEntry> {
+    let mut entries: Vec<AutostartEntry> = Vec::new();
+
+    for (path, config) in configs.files() {
+        let mut enabled: BTreeMap<usize, String> = config
+            .assignments()
+            .into_iter()
+            .filter(|assignment| assignment.key == "exec-once")
+            .map(|assignment| (assignment.line, assignment.value))
+            .collect();
+
+        for index in 0..config.line_count() {
+            let line: &str = config.line(index).unwrap_or_default();
+
+            let entry: Option<(String, bool)> = match DISABLED_RE.captures(line) {
+                Some(captures) => Some((captures[2].to_string(), false)),
+                None => enabled.remove(&index).map(|command| (command, true)),
+            };
+
+            if let Some((command, enabled)) = entry {
+                entries.push(AutostartEntry {
+                    file: path.to_path_buf(),
+                    line: index,
+                    command,
+                    enabled,
+                });
+            }
+        }
+    }
+
+    entries
+}
+
+// Entries in `user-*.conf` files were added by the user, everything else comes with the dotfiles
+pub fn is_user_entry(entry: &AutostartEntry) -> bool {
+    entry
+        .file
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("user-"))
+}
+
+// Appends an entry to one of the loaded files
+pub fn add_autostart_entry(configs: &mut ConfigSet, file: &Path, command: &str) -> io::Result<()> {
+    configs.edit_each(|path, config| {
+        if path != file {
+            return Ok(false);
+        }
+
+        config.push("exec-once", command);
+
+        Ok(true)
+    })
+}
+
+// Comments an entry out, or back in when `enabled` is set
+pub fn set_autostart_enabled(
+    configs: &mut ConfigSet,
+    entry: &AutostartEntry,
+    enabled: bool,
+) -> io::Result<()> {
+    configs.edit_each(|path, config| {
+        if path != entry.file {
+            return Ok(false);
+        }
+
+        let line: &str = config.line(entry.line).unwrap_or_default();
+        let indent: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+
+        let line: String = match enabled {
+            true => format!("{}exec-once = {}", indent, entry.command),
+            false => format!("{}# exec-once = {}", indent, entry.command),
+        };
+
+        config.replace_line(entry.line, &line)?;
+
+        Ok(true)
+    })
+}
+
+pub fn remove_autostart_entry(configs: &mut ConfigSet, entry: &AutostartEntry) -> io::Result<()> {
+    configs.edit_each(|path, config| {
+        if path != entry.file {
+            return Ok(false);
+        }
+
+        config.remove_line(entry.line)?;
+
+        Ok(true)
+    })
+}
+
+// Extracts the executables of a shell command like `waybar & hyprpaper` or `[workspace 2 silent] firefox`
+pub fn command_executables(command: &str) -> Vec<String> {
+    let command: &str = match command.trim_start().strip_prefix('[') {
+        Some(rest) => rest.split_once(']').map_or(rest, |(_, command)| command),
+        None => command,
+    };
+
+    command
+        .split(['&', ';', '|'])
+        // Skip leading environment assignments like `FOO=bar command`
+        .filter_map(|part| part.split_whitespace().find(|word| !word.contains('=')))
+        .map(String::from)
+        .collect()
+}
@@ -0,0 +1,229 @@
+use crate::{
+    hypr::{config::expand_variables, includes::ConfigSet},
+    Bind, BindConflicts,
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+// Order in which modifiers are written, aliases are mapped to these names
+const MODIFIERS: &[&str] = &[
+    "SUPER", "CTRL", "ALT", "SHIFT", "CAPS", "MOD2", "MOD3", "MOD5",
+];
+
+// Reads every `bind*` line in evaluation order with all variables expanded
+pub fn parse_binds(configs: &ConfigSet) -> Vec<Bind> {
+    let mut binds: Vec<Bind> = Vec::new();
+    let mut variables: BTreeMap<String, String> = BTreeMap::new();
+    let mut submap: Option<String> = None;
+
+    for (_, assignment) in configs.assignments() {
+        let key: &str = assignment.key.as_str();
+        let value: String = expand_variables(&assignment.value, &variables);
+
+        if let Some(name) = key.strip_prefix('$') {
+            variables.insert(name.to_string(), value);
+            continue;
+        }
+
+        if key == "submap" {
+            submap = (value != "reset").then_some(value);
+            continue;
+        }
+
+        let Some(flags) = key.strip_prefix("bind") else {
+            continue;
+        };
+
+        if let Some(bind) = parse_bind(flags, &value, submap.clone()) {
+            binds.push(bind);
+        }
+    }
+
+    binds
+}
+
+// Compares the binds of an existing config with the ones replacing them
+pub fn compare_binds(old: &[Bind], new: &[Bind]) -> BindConflicts {
+    let mut conflicts: BindConflicts = BindConflicts {
+        changed: Vec::new(),
+        collisions: Vec::new(),
+        user_only: Vec::new(),
+    };
+
+    for bind in old {
+        let replacements: Vec<&Bind> = new.iter().filter(|new| new.same_keys(bind)).collect();
+
+        if replacements.is_empty() {
+            conflicts.user_only.push(bind.clone());
+        } else if !replacements.iter().any(|new| new.same_action(bind)) {
+            conflicts
+                .changed
+                .push((bind.clone(), replacements[0].clone()));
+        }
+    }
+
+    for (index, bind) in new.iter().enumerate() {
+        if let Some(first) = new[..index].iter().find(|first| first.same_keys(bind)) {
+            conflicts.collisions.push((first.clone(), bind.clone()));
+        }
+    }
+
+    conflicts
+}
+
+// `bind[flags] = MODS, key, dispatcher, arg` or, with the `d` flag, `MODS, key, description, dispatcher, arg`
+fn parse_bind(flags: &str, value: &str, submap: Option<String>) -> Option<Bind> {
+    if !flags.chars().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+
+    let has_description: bool = flags.contains('d');
+    let fields: usize = if has_description { 5 } else { 4 };
+    let mut parts = value.splitn(fields, ',').map(str::trim);
+
+    let mods: Vec<String> = normalize_mods(parts.next()?);
+    let key: String = normalize_key(parts.next()?);
+    let description: Option<String> = match has_description {
+        true => Some(parts.next()?.to_string()).filter(|description| !description.is_empty()),
+        false => None,
+    };
+    let dispatcher: String = parts.next()?.to_string();
+    let arg: String = parts.next().unwrap_or_default().to_string();
+
+    Some(Bind {
+        flags: flags.to_string(),
+        mods,
+        key,
+        dispatcher,
+        arg,
+        description,
+        submap,
+    })
+}
+
+// `$mainMod SHIFT`, `SUPER_SHIFT` and `SHIFT+WIN` all become [SUPER, SHIFT]
+fn normalize_mods(mods: &str) -> Vec<String> {
+    let mut normalized: Vec<String> = mods
+        .split(|c: char| c.is_whitespace() || c == '_' || c == '+' || c == '&')
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            match name.to_uppercase().as_str() {
+                "WIN" | "LOGO" | "MOD4" | "META" => "SUPER",
+                "CONTROL" => "CTRL",
+                "MOD1" => "ALT",
+                "LOCK" => "CAPS",
+                name => return name.to_string(),
+            }
+            .to_string()
+        })
+        .collect();
+
+    normalized.sort_by_key(|name| {
+        MODIFIERS
+            .iter()
+            .position(|modifier| modifier == name)
+            .unwrap_or(MODIFIERS.len())
+    });
+    normalized.dedup();
+
+    normalized
+}
+
+// Keysyms are matched case-insensitively, so single letters are compared in upper case
+fn normalize_key(key: &str) -> String {
+    match key.len() {
+        1 => key.to_uppercase(),
+        _ => key.to_string(),
+    }
+}
+
+// Comments out binds using one of the keys, or comments them back in when `enabled` is set
+pub fn set_binds_enabled(
+    configs: &mut ConfigSet,
+    keys: &[&str],
+    enabled: bool,
+) -> io::Result<Vec<(PathBuf, String)>> {
+    static COMMENTED_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^(\s*)#+\s*(bind[a-z]*\s*=.*)$").unwrap());
+
+    let mut changed: Vec<(PathBuf, String)> = Vec::new();
+
+    configs.edit_each(|path: &Path, config| {
+        let mut modified: bool = false;
+
+        for index in 0..config.line_count() {
+            let line: String = config.line(index).unwrap_or_default().to_string();
+            let uses_key = |line: &str| {
+                line.split(',')
+                    .nth(1)
+                    .is_some_and(|key| keys.contains(&key.trim()))
+            };
+
+            let replacement: Option<String> = match COMMENTED_RE.captures(&line) {
+                Some(captures) if enabled && uses_key(&captures[2]) => {
+                    Some(format!("{}{}", &captures[1], &captures[2]))
+                }
+                None if !enabled && line.trim_start().starts_with("bind") && uses_key(&line) => {
+                    let indent: usize = line.len() - line.trim_start().len();
+
+                    Some(format!("{}# {}", &line[..indent], &line[indent..]))
+                }
+                _ => None,
+            };
+
+            if let Some(replacement) = replacement {
+                config.replace_line(index, &replacement)?;
+                changed.push((path.to_path_buf(), replacement.trim().to_string()));
+                modified = true;
+            }
+        }
+
+        Ok(modified)
+    })?;
+
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bind(flags: &str, value: &str) -> Bind {
+        parse_bind(flags, value, None).unwrap()
+    }
+
+    #[test]
+    fn collisions_respect_trigger_flags() {
+        let binds: Vec<Bind> = vec![
+            bind("", "SUPER, SUPER_L, exec, wofi"),
+            bind("r", "SUPER, SUPER_L, exec, wofi --show drun"),
+            bind(
+                "",
+                ", XF86AudioMute, exec, wpctl set-mute @DEFAULT_SINK@ toggle",
+            ),
+            bind(
+                "l",
+                ", XF86AudioMute, exec, wpctl set-mute @DEFAULT_SINK@ toggle",
+            ),
+            bind("e", "SUPER, Q, killactive"),
+            bind("", "super, q, exec, kitty"),
+        ];
+
+        let conflicts: BindConflicts = compare_binds(&[], &binds);
+
+        assert_eq!(conflicts.collisions.len(), 1);
+        assert_eq!(
+            conflicts.collisions[0].0.to_line(),
+            "binde = SUPER, Q, killactive"
+        );
+        assert_eq!(
+            conflicts.collisions[0].1.to_line(),
+            "bind = SUPER, Q, exec, kitty"
+        );
+    }
+}
@@ -0,0 +1,588 @@
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+// A Hyprland config file which keeps every line as written, so edits round-trip without losing comments
+pub struct HyprlandConfig {
+    lines: Vec<Line>,
+}
+
+struct Line {
+    raw: String,
+    kind: LineKind,
+    // Category the line belongs to, e.g. `input:touchpad`; for `{` and `}` it's the category they open or close
+    section: String,
+}
+
+enum LineKind {
+    Empty,
+    Open(String),
+    Close,
+    Assign {
+        // Key including all categories, e.g. `input:kb_layout`
+        key: String,
+        // Position of the value inside the raw line
+        value: Range<usize>,
+    },
+}
+
+pub struct Assignment {
+    pub line: usize,
+    pub key: String,
+    pub value: String,
+}
+
+impl HyprlandConfig {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+            .map_err(|error| io::Error::new(error.kind(), format!("{}: {}", path.display(), error)))
+    }
+
+    pub fn parse(contents: &str) -> io::Result<Self> {
+        let mut lines: Vec<Line> = Vec::new();
+        let mut sections: Vec<String> = Vec::new();
+
+        for (index, raw) in contents.lines().enumerate() {
+            let mut section: String = sections.join(":");
+            let kind: LineKind = parse_line(raw, &section)
+                .ok_or_else(|| invalid_line(index, "expected `key = value`, `name {` or `}`"))?;
+
+            match &kind {
+                LineKind::Open(name) => {
+                    sections.push(name.clone());
+                    section = sections.join(":");
+                }
+                LineKind::Close => {
+                    sections
+                        .pop()
+                        .ok_or_else(|| invalid_line(index, "unexpected `}`"))?;
+                }
+                _ => {}
+            }
+
+            lines.push(Line {
+                raw: raw.to_string(),
+                kind,
+                section,
+            });
+        }
+
+        if let Some(section) = sections.last() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("category `{}` is never closed", section),
+            ));
+        }
+
+        Ok(Self { lines })
+    }
+
+    // Writes the config through a temporary file so a failed write never leaves a truncated config behind
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        // Dotfile managers often symlink the config, renaming onto the link would replace it with a regular file
+        let path: PathBuf = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let temp_path: PathBuf = path.with_extension("conf.tmp");
+
+        fs::write(&temp_path, self.to_string())?;
+        fs::rename(&temp_path, &path)
+    }
+
+    // All `key = value` lines in order, including `$variables` and keywords like `source` or `bind`
+    pub fn assignments(&self) -> Vec<Assignment> {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| match &line.kind {
+                LineKind::Assign { key, value } => Some(Assignment {
+                    line: index,
+                    key: key.clone(),
+                    value: unescape(&line.raw[value.clone()]),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Assignments of each `name { }` block of a category, e.g. every hypridle `listener` on its own
+    pub fn blocks(&self, section: &str) -> Vec<Vec<Assignment>> {
+        let mut blocks: Vec<Vec<Assignment>> = Vec::new();
+
+        for (index, line) in self.lines.iter().enumerate() {
+            if line.section != section {
+                continue;
+            }
+
+            match &line.kind {
+                LineKind::Open(_) => blocks.push(Vec::new()),
+                LineKind::Assign { key, value } => {
+                    if let Some(block) = blocks.last_mut() {
+                        block.push(Assignment {
+                            line: index,
+                            key: key.clone(),
+                            value: unescape(&line.raw[value.clone()]),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        blocks
+    }
+
+    // Values of every assignment to a key, e.g. all `exec-once` lines
+    pub fn get_all(&self, key: &str) -> Vec<String> {
+        self.assignments()
+            .into_iter()
+            .filter(|assignment| assignment.key == key)
+            .map(|assignment| assignment.value)
+            .collect()
+    }
+
+    // Changes the last definition of a key or adds it to its category, creating the category if needed
+    pub fn set(&mut self, key: &str, value: &str) {
+        let existing: Option<usize> = self
+            .assignments()
+            .into_iter()
+            .rev()
+            .find(|assignment| assignment.key == key)
+            .map(|assignment| assignment.line);
+
+        match existing {
+            Some(index) => self.set_line_value(index, value),
+            None => self.insert(key, value),
+        }
+    }
+
+    // Adds a new assignment after the last one with the same key, useful for keywords like `env` or `bind`
+    pub fn push(&mut self, key: &str, value: &str) {
+        let last: Option<usize> = self
+            .assignments()
+            .into_iter()
+            .rev()
+            .find(|assignment| assignment.key == key)
+            .map(|assignment| assignment.line);
+
+        match last {
+            Some(index) => {
+                let indent: String = indentation(&self.lines[index].raw);
+                let section: String = self.lines[index].section.clone();
+                let (_, name) = split_key(key);
+
+                self.insert_line(
+                    index + 1,
+                    format!("{}{} = {}", indent, name, escape(value)),
+                    &section,
+                );
+            }
+            None => self.insert(key, value),
+        }
+    }
+
+    pub fn line(&self, index: usize) -> Option<&str> {
+        self.lines.get(index).map(|line| line.raw.as_str())
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn remove_line(&mut self, index: usize) -> io::Result<()> {
+        if matches!(self.lines[index].kind, LineKind::Open(_) | LineKind::Close) {
+            return Err(invalid_line(index, "cannot remove a category line"));
+        }
+
+        self.lines.remove(index);
+
+        Ok(())
+    }
+
+    // Replaces a single line in place; the new line may not open or close a category
+    pub fn replace_line(&mut self, index: usize, raw: &str) -> io::Result<()> {
+        if matches!(self.lines[index].kind, LineKind::Open(_) | LineKind::Close) {
+            return Err(invalid_line(index, "cannot replace a category line"));
+        }
+
+        let section: String = self.lines[index].section.clone();
+        let kind: LineKind = match parse_line(raw, &section) {
+            Some(kind @ (LineKind::Empty | LineKind::Assign { .. })) => kind,
+            _ => {
+                return Err(invalid_line(
+                    index,
+                    "replacement must be a `key = value` line or a comment",
+                ))
+            }
+        };
+
+        self.lines[index] = Line {
+            raw: raw.to_string(),
+            kind,
+            section,
+        };
+
+        Ok(())
+    }
+
+    pub fn set_line_value(&mut self, index: usize, value: &str) {
+        let line: &mut Line = &mut self.lines[index];
+
+        if let LineKind::Assign { value: range, .. } = &mut line.kind {
+            let escaped: String = escape(value);
+
+            line.raw.replace_range(range.clone(), &escaped);
+            range.end = range.start + escaped.len();
+        }
+    }
+
+    // Gives a line a new key and value; keys of another category are moved into that category
+    pub fn rename_line(&mut self, index: usize, key: &str, value: &str) -> io::Result<()> {
+        let section: String = self.lines[index].section.clone();
+        let name: Option<&str> = match section.as_str() {
+            "" => Some(key),
+            section => key
+                .strip_prefix(section)
+                .and_then(|name| name.strip_prefix(':'))
+                .filter(|name| !name.contains(':')),
+        };
+
+        match name {
+            Some(name) => {
+                let raw: &str = &self.lines[index].raw;
+                // Keeps a trailing comment
+                let comment: String = raw[strip_comment(raw).trim_end().len()..].to_string();
+                let indent: String = indentation(raw);
+
+                self.replace_line(
+                    index,
+                    &format!("{}{} = {}{}", indent, name, escape(value), comment),
+                )
+            }
+            None => {
+                self.remove_line(index)?;
+                self.insert(key, value);
+
+                Ok(())
+            }
+        }
+    }
+
+    // Inserts a new key into the deepest existing category of its path and opens the missing ones
+    fn insert(&mut self, key: &str, value: &str) {
+        let (section, name) = split_key(key);
+        let categories: Vec<&str> = section.split(':').filter(|c| !c.is_empty()).collect();
+
+        let mut depth: usize = categories.len();
+        let mut close: Option<usize> = None;
+
+        while depth > 0 {
+            close = self.section_close(&categories[..depth].join(":"));
+
+            if close.is_some() {
+                break;
+            }
+
+            depth -= 1;
+        }
+
+        let mut index: usize = close.unwrap_or(self.lines.len());
+        let mut indent: String = match close {
+            Some(close) => format!("{}    ", indentation(&self.lines[close].raw)),
+            None => String::new(),
+        };
+
+        // Separate new top-level categories from what comes before them
+        if close.is_none()
+            && depth < categories.len()
+            && self
+                .lines
+                .last()
+                .is_some_and(|line| !line.raw.trim().is_empty())
+        {
+            self.insert_line(index, String::new(), "");
+            index += 1;
+        }
+
+        for level in depth..categories.len() {
+            self.insert_line(
+                index,
+                format!("{}{} {{", indent, categories[level]),
+                &categories[..=level].join(":"),
+            );
+            index += 1;
+            indent.push_str("    ");
+        }
+
+        self.insert_line(
+            index,
+            format!("{}{} = {}", indent, name, escape(value)),
+            section,
+        );
+        index += 1;
+
+        for level in (depth..categories.len()).rev() {
+            indent.truncate(indent.len() - 4);
+
+            self.insert_line(
+                index,
+                format!("{}}}", indent),
+                &categories[..=level].join(":"),
+            );
+            index += 1;
+        }
+    }
+
+    fn insert_line(&mut self, index: usize, raw: String, section: &str) {
+        let kind: LineKind = parse_line(&raw, section).unwrap_or(LineKind::Empty);
+
+        self.lines.insert(
+            index,
+            Line {
+                raw,
+                kind,
+                section: section.to_string(),
+            },
+        );
+    }
+
+    // Index of the closing brace of the last block of a category
+    fn section_close(&self, section: &str) -> Option<usize> {
+        self.lines
+            .iter()
+            .rposition(|line| matches!(line.kind, LineKind::Close) && line.section == section)
+    }
+}
+
+impl fmt::Display for HyprlandConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line.raw)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_line(raw: &str, section: &str) -> Option<LineKind> {
+    let content: &str = strip_comment(raw);
+    let trimmed: &str = content.trim();
+
+    if trimmed.is_empty() {
+        return Some(LineKind::Empty);
+    }
+
+    if trimmed == "}" {
+        return Some(LineKind::Close);
+    }
+
+    // A value may end in `{` as well, e.g. `bind = SUPER, E, exec, sh -c '... {'`
+    if let Some(name) = trimmed.strip_suffix('{').filter(|name| !name.contains('=')) {
+        let name: &str = name.trim();
+
+        return (!name.is_empty()).then(|| LineKind::Open(name.to_string()));
+    }
+
+    let (key, _) = content.split_once('=')?;
+    let key: &str = key.trim();
+
+    if key.is_empty() {
+        return None;
+    }
+
+    let value_start: usize = content.find('=')? + 1;
+    let value_offset: usize =
+        content[value_start..].len() - content[value_start..].trim_start().len();
+    let start: usize = value_start + value_offset;
+    let end: usize = content.trim_end().len().max(start);
+
+    let key: String = match section {
+        "" => key.to_string(),
+        _ => format!("{}:{}", section, key),
+    };
+
+    Some(LineKind::Assign {
+        key,
+        value: start..end,
+    })
+}
+
+// Removes a trailing comment; `##` is an escaped `#` and stays part of the line
+fn strip_comment(raw: &str) -> &str {
+    let bytes: &[u8] = raw.as_bytes();
+    let mut index: usize = 0;
+
+    while index < bytes.len() {
+        if bytes[index] == b'#' {
+            if bytes.get(index + 1) == Some(&b'#') {
+                index += 2;
+                continue;
+            }
+
+            return &raw[..index];
+        }
+
+        index += 1;
+    }
+
+    raw
+}
+
+fn escape(value: &str) -> String {
+    value.replace('#', "##")
+}
+
+fn unescape(value: &str) -> String {
+    value.replace("##", "#")
+}
+
+fn indentation(raw: &str) -> String {
+    raw.chars().take_while(|c| c.is_whitespace()).collect()
+}
+
+// Replaces `$name` with the value of the variable, leaving unknown variables untouched
+pub fn expand_variables(value: &str, variables: &BTreeMap<String, String>) -> String {
+    static VARIABLE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$(\w+)").unwrap());
+
+    VARIABLE_RE
+        .replace_all(value, |captures: &Captures| {
+            variables
+                .get(&captures[1])
+                .cloned()
+                .unwrap_or_else(|| captures[0].to_string())
+        })
+        .to_string()
+}
+
+// Splits `input:touchpad:natural_scroll` into `input:touchpad` and `natural_scroll`
+pub fn split_key(key: &str) -> (&str, &str) {
+    key.rsplit_once(':').unwrap_or(("", key))
+}
+
+fn invalid_line(index: usize, message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {}: {}", index + 1, message),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+
+    const CONFIG: &str = "\
+# Variables
+$terminal = kitty
+$accent = rgb(a7c080) # green
+
+general {
+    border_size = 2 # pixels
+    col.active_border = $accent
+
+    snap {
+        enabled = true
+    }
+}
+
+input {
+    kb_layout = de
+}
+
+bind = SUPER, Q, exec, $terminal
+bind = SUPER, E, exec, sh -c 'echo {'
+windowrule = float, title:^(Issue ##1)$ # escaped hash
+";
+
+    #[test]
+    fn parse_and_save_round_trip() {
+        let config: HyprlandConfig = HyprlandConfig::parse(CONFIG).unwrap();
+
+        assert_eq!(config.to_string(), CONFIG);
+        assert_eq!(config.get_all("$accent"), ["rgb(a7c080)"]);
+        assert_eq!(config.get_all("general:border_size"), ["2"]);
+        assert_eq!(config.get_all("general:snap:enabled"), ["true"]);
+        assert_eq!(
+            config.get_all("bind"),
+            [
+                "SUPER, Q, exec, $terminal",
+                "SUPER, E, exec, sh -c 'echo {'"
+            ]
+        );
+        assert_eq!(config.get_all("windowrule"), ["float, title:^(Issue #1)$"]);
+    }
+
+    #[test]
+    fn edits_keep_comments_and_escapes() {
+        let mut config: HyprlandConfig = HyprlandConfig::parse(CONFIG).unwrap();
+
+        config.set("general:border_size", "3");
+        config.set("input:kb_variant", "nodeadkeys");
+        config.set("windowrule", "float, title:^(Issue #2)$");
+        config.set("decoration:rounding", "8");
+
+        let saved: String = config.to_string();
+
+        assert!(saved.contains("    border_size = 3 # pixels\n"));
+        assert!(saved.contains("    kb_layout = de\n    kb_variant = nodeadkeys\n}"));
+        assert!(saved.contains("windowrule = float, title:^(Issue ##2)$ # escaped hash\n"));
+        assert!(saved.ends_with("\ndecoration {\n    rounding = 8\n}\n"));
+        assert_eq!(
+            HyprlandConfig::parse(&saved).unwrap().get_all("windowrule"),
+            ["float, title:^(Issue #2)$"]
+        );
+    }
+
+    #[test]
+    fn variables_expand() {
+        let config: HyprlandConfig = HyprlandConfig::parse(CONFIG).unwrap();
+        let variables: BTreeMap<String, String> = config
+            .assignments()
+            .into_iter()
+            .filter_map(|assignment| {
+                let name: &str = assignment.key.strip_prefix('$')?;
+                Some((name.to_string(), assignment.value))
+            })
+            .collect();
+
+        assert_eq!(
+            expand_variables("SUPER, Q, exec, $terminal $unknown", &variables),
+            "SUPER, Q, exec, kitty $unknown"
+        );
+    }
+
+    #[test]
+    fn unbalanced_categories_are_rejected() {
+        assert!(HyprlandConfig::parse("input {\n").is_err());
+        assert!(HyprlandConfig::parse("}\n").is_err());
+    }
+
+    #[test]
+    fn save_writes_through_symlinks() {
+        let dir: PathBuf = env::temp_dir().join(format!("autoricer-config-{}", process::id()));
+        let target: PathBuf = dir.join("dotfiles.conf");
+        let link: PathBuf = dir.join("hyprland.conf");
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&target, CONFIG).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let mut config: HyprlandConfig = HyprlandConfig::load(&link).unwrap();
+
+        config.set("input:kb_layout", "us");
+        config.save(&link).unwrap();
+
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert!(fs::read_to_string(&target)
+            .unwrap()
+            .contains("kb_layout = us"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{hypr::includes::ConfigSet, EnvGroup, EnvGroupStatus};
+use regex::Regex;
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+// Name of the variable of an `env = NAME,value` line
+pub fn env_name(value: &str) -> &str {
+    value.split(',').next().unwrap_or_default().trim()
+}
+
+// Sets `env = NAME,value`, reusing an active or commented out line for the variable before adding a new one
+pub fn set_env(configs: &mut ConfigSet, name: &str, value: &str) -> io::Result<PathBuf> {
+    let assignment: String = format!("{},{}", name, value);
+
+    if let Some(path) = configs.set_where("env", |value| env_name(value) == name, &assignment) {
+        return Ok(path.to_path_buf());
+    }
+
+    let commented_re: Regex = Regex::new(&format!(
+        r"^(\s*)#+\s*env\s*=\s*{}\s*,",
+        regex::escape(name)
+    ))
+    .unwrap();
+    let mut uncommented: Option<PathBuf> = None;
+
+    configs.edit_each(|path: &Path, config| {
+        if uncommented.is_some() {
+            return Ok(false);
+        }
+
+        for index in 0..config.line_count() {
+            let line: &str = config.line(index).unwrap_or_default();
+
+            if let Some(captures) = commented_re.captures(line) {
+                let line: String = format!("{}env = {}", &captures[1], assignment);
+
+                config.replace_line(index, &line)?;
+                uncommented = Some(path.to_path_buf());
+
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    })?;
+
+    match uncommented {
+        Some(path) => Ok(path),
+        None => Ok(configs.push("env", &assignment).to_path_buf()),
+    }
+}
+
+// Sets every variable of a group except the skipped ones; running it again changes nothing
+pub fn enable_env_group(
+    configs: &mut ConfigSet,
+    group: &EnvGroup,
+    skipped: &[&str],
+) -> io::Result<Vec<(PathBuf, String)>> {
+    let mut changed: Vec<(PathBuf, String)> = Vec::new();
+
+    for &(name, value) in group
+        .vars
+        .iter()
+        .filter(|(name, _)| !skipped.contains(name))
+    {
+        let assignment: String = format!("{},{}", name, value);
+
+        if configs.get_all("env").contains(&assignment) {
+            continue;
+        }
+
+        changed.push((set_env(configs, name, value)?, assignment));
+    }
+
+    // Variables skipped now may still be left over from an earlier run
+    comment_out_env(configs, |name| skipped.contains(&name))?;
+
+    Ok(changed)
+}
+
+// Comments out all `env` lines of a group's variables, so enabling the group again restores them
+pub fn disable_env_group(
+    configs: &mut ConfigSet,
+    group: &EnvGroup,
+) -> io::Result<Vec<(PathBuf, String)>> {
+    comment_out_env(configs, |name| {
+        group.vars.iter().any(|(var, _)| *var == name)
+    })
+}
+
+// Turns matching `env = NAME,value` lines into `# env = NAME,value`
+fn comment_out_env(
+    configs: &mut ConfigSet,
+    matches: impl Fn(&str) -> bool,
+) -> io::Result<Vec<(PathBuf, String)>> {
+    let mut commented: Vec<(PathBuf, String)> = Vec::new();
+
+    configs.edit_each(|path: &Path, config| {
+        let mut modified: bool = false;
+
+        for assignment in config.assignments() {
+            if assignment.key != "env" || !matches(env_name(&assignment.value)) {
+                continue;
+            }
+
+            let line: String = config.line(assignment.line).unwrap_or_default().to_string();
+            let indent: usize = line.len() - line.trim_start().len();
+
+            config.replace_line(
+                assignment.line,
+                &format!("{}# {}", &line[..indent], &line[indent..]),
+            )?;
+            commented.push((path.to_path_buf(), assignment.value));
+            modified = true;
+        }
+
+        Ok(modified)
+    })?;
+
+    Ok(commented)
+}
+
+pub fn env_group_status(configs: &ConfigSet, group: &EnvGroup) -> EnvGroupStatus {
+    let env: Vec<String> = configs.get_all("env");
+    let enabled: usize = group
+        .vars
+        .iter()
+        .filter(|(name, value)| env.contains(&format!("{},{}", name, value)))
+        .count();
+
+    match enabled {
+        0 => EnvGroupStatus::Disabled,
+        enabled if enabled == group.vars.len() => EnvGroupStatus::Enabled,
+        _ => EnvGroupStatus::Partial,
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{hypr::config::HyprlandConfig, utils::contents::get_idle_timeouts};
+
+// Sets the timeout of every hypridle `listener` block whose `on-timeout` matches an entry of the table
+pub fn set_idle_timeouts(config: &mut HyprlandConfig, laptop: bool) -> Vec<(String, String)> {
+    let mut changed: Vec<(String, String)> = Vec::new();
+
+    for block in config.blocks("listener") {
+        let (mut timeout, mut action) = (None, None);
+
+        for assignment in block {
+            match assignment.key.as_str() {
+                "listener:timeout" => timeout = Some(assignment),
+                "listener:on-timeout" => action = Some(assignment.value),
+                _ => {}
+            }
+        }
+
+        let (Some(timeout), Some(action)) = (timeout, action) else {
+            continue;
+        };
+
+        let Some((_, laptop_timeout, desktop_timeout)) = get_idle_timeouts()
+            .into_iter()
+            .find(|(command, _, _)| action.contains(command))
+        else {
+            continue;
+        };
+
+        let seconds: String = match laptop {
+            true => laptop_timeout.to_string(),
+            false => desktop_timeout.to_string(),
+        };
+
+        if timeout.value != seconds {
+            config.set_line_value(timeout.line, &seconds);
+            changed.push((action, seconds));
+        }
+    }
+
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjusts_the_timeout_of_each_listener() {
+        let mut config: HyprlandConfig = HyprlandConfig::parse(
+            "\
+general {
+    lock_cmd = pidof hyprlock || hyprlock
+}
+
+listener { # dim the screen
+    timeout = 150
+    on-timeout = brightnessctl -s set 10
+    on-resume = brightnessctl -r
+}
+
+listener {
+    on-timeout = loginctl lock-session
+    timeout = 300
+}
+
+listener {
+    timeout = 60
+    on-timeout = notify-send idle
+}
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            set_idle_timeouts(&mut config, false),
+            [
+                (String::from("brightnessctl -s set 10"), String::from("300")),
+                (String::from("loginctl lock-session"), String::from("600")),
+            ]
+        );
+        assert_eq!(config.get_all("listener:timeout"), ["300", "600", "60"]);
+        assert!(set_idle_timeouts(&mut config, false).is_empty());
+    }
+}
@@ -0,0 +1,472 @@
+use crate::{hypr::config::*, utils::helper_functions::expand_home};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+// A Hyprland config together with every file it pulls in through `source =` lines
+pub struct ConfigSet {
+    files: Vec<ConfigFile>,
+    // Assignments in the order Hyprland evaluates them, as (file index, assignment)
+    order: Vec<(usize, Assignment)>,
+    pub missing: Vec<PathBuf>,
+    home: PathBuf,
+    // Sources inside the first directory are read from the second one
+    relocate: Option<(PathBuf, PathBuf)>,
+}
+
+struct ConfigFile {
+    path: PathBuf,
+    config: HyprlandConfig,
+    modified: bool,
+}
+
+impl ConfigSet {
+    pub fn load(root: &Path, home: &Path) -> io::Result<Self> {
+        Self::load_with(root, home, None)
+    }
+
+    // Loads a config which isn't installed yet, so sources pointing to where it will be installed are read next to `root`
+    pub fn load_staged(root: &Path, home: &Path, installed: &Path) -> io::Result<Self> {
+        let staged: PathBuf = root.parent().unwrap_or(Path::new(".")).to_path_buf();
+
+        Self::load_with(root, home, Some((installed.to_path_buf(), staged)))
+    }
+
+    fn load_with(
+        root: &Path,
+        home: &Path,
+        relocate: Option<(PathBuf, PathBuf)>,
+    ) -> io::Result<Self> {
+        let mut set: Self = Self {
+            files: Vec::new(),
+            order: Vec::new(),
+            missing: Vec::new(),
+            home: home.to_path_buf(),
+            relocate,
+        };
+        let mut variables: BTreeMap<String, String> = BTreeMap::new();
+
+        set.load_file(root, &mut variables)?;
+        set.reindex();
+
+        Ok(set)
+    }
+
+    // Sourced files are evaluated in place of their `source =` line, so they are walked depth first
+    fn load_file(
+        &mut self,
+        path: &Path,
+        variables: &mut BTreeMap<String, String>,
+    ) -> io::Result<()> {
+        let config: HyprlandConfig = HyprlandConfig::load(path)?;
+        let assignments: Vec<Assignment> = config.assignments();
+
+        self.files.push(ConfigFile {
+            path: path.to_path_buf(),
+            config,
+            modified: false,
+        });
+
+        for assignment in assignments {
+            if let Some(name) = assignment.key.strip_prefix('$') {
+                variables.insert(
+                    name.to_string(),
+                    expand_variables(&assignment.value, variables),
+                );
+            }
+
+            if assignment.key != "source" {
+                continue;
+            }
+
+            for source in self.sources(path, &assignment.value, variables)? {
+                if self.files.iter().any(|file| file.path == source) {
+                    continue;
+                }
+
+                if source.exists() {
+                    self.load_file(&source, variables)?;
+                } else {
+                    self.missing.push(source);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Files a `source = value` line in `path` pulls in
+    fn sources(
+        &self,
+        path: &Path,
+        value: &str,
+        variables: &BTreeMap<String, String>,
+    ) -> io::Result<Vec<PathBuf>> {
+        let mut source: String = expand_variables(value, variables);
+
+        if let Some((installed, staged)) = &self.relocate {
+            if let Ok(rest) = expand_home(&source, &self.home).strip_prefix(installed) {
+                source = staged.join(rest).to_string_lossy().to_string();
+            }
+        }
+
+        resolve_source(&source, &self.home, path.parent().unwrap_or(Path::new(".")))
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|file| file.path.as_path())
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = (&Path, &HyprlandConfig)> {
+        self.files
+            .iter()
+            .map(|file| (file.path.as_path(), &file.config))
+    }
+
+    // Every assignment in evaluation order together with the file it lives in
+    pub fn assignments(&self) -> impl Iterator<Item = (&Path, &Assignment)> {
+        self.order
+            .iter()
+            .map(|(index, assignment)| (self.files[*index].path.as_path(), assignment))
+    }
+
+    // Hyprland applies the last definition of a key, so that's the one returned
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.order
+            .iter()
+            .rev()
+            .find(|(_, assignment)| assignment.key == key)
+            .map(|(_, assignment)| assignment.value.clone())
+    }
+
+    // Values of a key across all files in evaluation order
+    pub fn get_all(&self, key: &str) -> Vec<String> {
+        self.order
+            .iter()
+            .filter(|(_, assignment)| assignment.key == key)
+            .map(|(_, assignment)| assignment.value.clone())
+            .collect()
+    }
+
+    // Values of all `$variables` with earlier variables expanded, as Hyprland sees them after loading the config
+    pub fn variables(&self) -> BTreeMap<String, String> {
+        let mut variables: BTreeMap<String, String> = BTreeMap::new();
+
+        for (_, assignment) in self.order.iter() {
+            if let Some(name) = assignment.key.strip_prefix('$') {
+                let value: String = expand_variables(&assignment.value, &variables);
+
+                variables.insert(name.to_string(), value);
+            }
+        }
+
+        variables
+    }
+
+    // Edits a key inside the file defining it, or the main config if it isn't defined anywhere
+    pub fn set(&mut self, key: &str, value: &str) -> &Path {
+        let index: usize = self
+            .order
+            .iter()
+            .rev()
+            .find(|(_, assignment)| assignment.key == key)
+            .map_or(0, |(index, _)| *index);
+
+        self.files[index].config.set(key, value);
+        self.files[index].modified = true;
+        self.reindex();
+
+        &self.files[index].path
+    }
+
+    // Edits the last assignment of a key whose value matches, e.g. the `env` line of one variable
+    pub fn set_where(
+        &mut self,
+        key: &str,
+        matches: impl Fn(&str) -> bool,
+        value: &str,
+    ) -> Option<&Path> {
+        let (index, line) = self
+            .order
+            .iter()
+            .rev()
+            .find(|(_, assignment)| assignment.key == key && matches(&assignment.value))
+            .map(|(index, assignment)| (*index, assignment.line))?;
+
+        self.files[index].config.set_line_value(line, value);
+        self.files[index].modified = true;
+        self.reindex();
+
+        Some(&self.files[index].path)
+    }
+
+    // Adds an assignment next to the last one with the same key, or to the main config
+    pub fn push(&mut self, key: &str, value: &str) -> &Path {
+        let index: usize = self
+            .order
+            .iter()
+            .rev()
+            .find(|(_, assignment)| assignment.key == key)
+            .map_or(0, |(index, _)| *index);
+
+        self.files[index].config.push(key, value);
+        self.files[index].modified = true;
+        self.reindex();
+
+        &self.files[index].path
+    }
+
+    // Applies a change to every file; `edit` returns whether it modified the config
+    pub fn edit_each(
+        &mut self,
+        mut edit: impl FnMut(&Path, &mut HyprlandConfig) -> io::Result<bool>,
+    ) -> io::Result<()> {
+        for index in 0..self.files.len() {
+            let file: &mut ConfigFile = &mut self.files[index];
+
+            if edit(&file.path, &mut file.config)? {
+                file.modified = true;
+                self.reindex();
+            }
+        }
+
+        Ok(())
+    }
+
+    // Saves all modified files and returns their paths
+    pub fn save(&mut self) -> io::Result<Vec<PathBuf>> {
+        let mut saved: Vec<PathBuf> = Vec::new();
+
+        for file in self.files.iter_mut().filter(|file| file.modified) {
+            file.config.save(&file.path)?;
+            file.modified = false;
+            saved.push(file.path.clone());
+        }
+
+        Ok(saved)
+    }
+
+    // Rebuilds the evaluation order from the loaded files, since an edit can move lines around a `source =`
+    fn reindex(&mut self) {
+        let mut order: Vec<(usize, Assignment)> = Vec::new();
+        let mut visited: Vec<usize> = Vec::new();
+        let mut variables: BTreeMap<String, String> = BTreeMap::new();
+
+        self.walk(0, &mut variables, &mut visited, &mut order);
+        self.order = order;
+    }
+
+    fn walk(
+        &self,
+        index: usize,
+        variables: &mut BTreeMap<String, String>,
+        visited: &mut Vec<usize>,
+        order: &mut Vec<(usize, Assignment)>,
+    ) {
+        visited.push(index);
+
+        let file: &ConfigFile = &self.files[index];
+
+        for assignment in file.config.assignments() {
+            if let Some(name) = assignment.key.strip_prefix('$') {
+                variables.insert(
+                    name.to_string(),
+                    expand_variables(&assignment.value, variables),
+                );
+            }
+
+            if assignment.key == "source" {
+                // Only files which were loaded can be walked, new sources are picked up by loading again
+                for source in self
+                    .sources(&file.path, &assignment.value, variables)
+                    .unwrap_or_default()
+                {
+                    if let Some(child) = self.files.iter().position(|file| file.path == source) {
+                        if !visited.contains(&child) {
+                            self.walk(child, variables, visited, order);
+                        }
+                    }
+                }
+            }
+
+            order.push((index, assignment));
+        }
+    }
+}
+
+// Expands `~` and glob patterns like hyprlang does; relative paths are relative to the file containing the `source =` line
+pub fn resolve_source(source: &str, home: &Path, base_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let path: PathBuf = base_dir.join(expand_home(source.trim(), home));
+
+    if !path.to_string_lossy().contains(['*', '?', '[']) {
+        return Ok(vec![path]);
+    }
+
+    let mut paths: Vec<PathBuf> = vec![PathBuf::new()];
+
+    for component in path.components() {
+        let pattern: String = component.as_os_str().to_string_lossy().to_string();
+
+        if !pattern.contains(['*', '?', '[']) {
+            paths.iter_mut().for_each(|path| path.push(&pattern));
+            continue;
+        }
+
+        let mut matches: Vec<PathBuf> = Vec::new();
+
+        for dir in paths.iter().filter(|dir| dir.is_dir()) {
+            for entry in fs::read_dir(dir)?.flatten() {
+                let name: String = entry.file_name().to_string_lossy().to_string();
+
+                // Like glob(3), wildcards don't match hidden files unless the pattern starts with a dot
+                if name.starts_with('.') && !pattern.starts_with('.') {
+                    continue;
+                }
+
+                if glob_match(&pattern, &name) {
+                    matches.push(entry.path());
+                }
+            }
+        }
+
+        paths = matches;
+    }
+
+    paths.retain(|path| path.is_file());
+    paths.sort();
+
+    Ok(paths)
+}
+
+// Matches a single path component against `*`, `?` and `[...]` / `[!...]` character classes
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    match_from(&pattern, &name)
+}
+
+fn match_from(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).any(|skip| match_from(&pattern[1..], &name[skip..])),
+        Some('?') => !name.is_empty() && match_from(&pattern[1..], &name[1..]),
+        Some('[') => {
+            let Some(end) = pattern
+                .iter()
+                .skip(2)
+                .position(|&c| c == ']')
+                .map(|end| end + 2)
+            else {
+                return name.first() == Some(&'[') && match_from(&pattern[1..], &name[1..]);
+            };
+            let Some(&c) = name.first() else {
+                return false;
+            };
+
+            let (negated, class) = match pattern[1] {
+                '!' | '^' => (true, &pattern[2..end]),
+                _ => (false, &pattern[1..end]),
+            };
+
+            let mut matched: bool = false;
+            let mut index: usize = 0;
+
+            while index < class.len() {
+                if index + 2 < class.len() && class[index + 1] == '-' {
+                    matched |= (class[index]..=class[index + 2]).contains(&c);
+                    index += 3;
+                } else {
+                    matched |= class[index] == c;
+                    index += 1;
+                }
+            }
+
+            matched != negated && match_from(&pattern[end + 1..], &name[1..])
+        }
+        Some(&c) => name.first() == Some(&c) && match_from(&pattern[1..], &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob_match("*.conf", "colors.conf"));
+        assert!(!glob_match("*.conf", "colors.conf.bak"));
+        assert!(glob_match("bind?.conf", "binds.conf"));
+        assert!(glob_match("[a-c]*.conf", "binds.conf"));
+        assert!(!glob_match("[!a-c]*.conf", "binds.conf"));
+    }
+
+    #[test]
+    fn relative_sources_resolve_next_to_the_sourcing_file() {
+        let dir: PathBuf = env::temp_dir().join(format!("autoricer-includes-{}", process::id()));
+        let hypr: PathBuf = dir.join("hypr");
+
+        fs::create_dir_all(hypr.join("conf.d")).unwrap();
+        fs::write(
+            hypr.join("hyprland.conf"),
+            "source = colors.conf\nsource = conf.d/*.conf\n",
+        )
+        .unwrap();
+        fs::write(hypr.join("colors.conf"), "$accent = green\n").unwrap();
+        fs::write(hypr.join("conf.d/input.conf"), "input:kb_layout = de\n").unwrap();
+        fs::write(hypr.join("conf.d/.hidden.conf"), "input:kb_layout = fr\n").unwrap();
+
+        let configs: ConfigSet = ConfigSet::load(&hypr.join("hyprland.conf"), &dir).unwrap();
+
+        assert!(configs.missing.is_empty());
+        assert_eq!(configs.get("$accent").as_deref(), Some("green"));
+        assert_eq!(
+            configs.variables().get("accent").map(String::as_str),
+            Some("green")
+        );
+        assert_eq!(configs.get("input:kb_layout").as_deref(), Some("de"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn edits_keep_the_evaluation_order() {
+        let dir: PathBuf = env::temp_dir().join(format!("autoricer-order-{}", process::id()));
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("hyprland.conf"),
+            "$x = 1\nsource = a.conf\ninput {\n    kb_layout = us\n}\n",
+        )
+        .unwrap();
+        fs::write(dir.join("a.conf"), "input:kb_layout = de\n").unwrap();
+
+        let mut configs: ConfigSet = ConfigSet::load(&dir.join("hyprland.conf"), &dir).unwrap();
+
+        assert_eq!(configs.get("input:kb_layout").as_deref(), Some("us"));
+
+        configs.set("$mainMod", "ALT");
+        configs.push("bind", "$mainMod, Q, killactive");
+
+        assert_eq!(configs.get("input:kb_layout").as_deref(), Some("us"));
+        assert_eq!(
+            configs
+                .assignments()
+                .map(|(_, assignment)| assignment.key.as_str())
+                .collect::<Vec<&str>>(),
+            [
+                "$x",
+                "input:kb_layout",
+                "source",
+                "input:kb_layout",
+                "$mainMod",
+                "bind"
+            ]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,116 @@
+use std::{
+    env,
+    io::{self, Read, Write},
+    net::Shutdown,
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+// Locates the control socket of the running Hyprland instance, if any
+pub fn hyprland_socket() -> Option<PathBuf> {
+    instance_socket(".socket.sock")
+}
+
+// hyprpaper puts its socket next to Hyprland's
+pub fn hyprpaper_socket() -> Option<PathBuf> {
+    instance_socket(".hyprpaper.sock")
+}
+
+fn instance_socket(name: &str) -> Option<PathBuf> {
+    let signature: String = env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()?;
+
+    // Hyprland moved its sockets from /tmp/hypr to $XDG_RUNTIME_DIR/hypr in v0.40
+    let runtime_dir: PathBuf = env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/tmp"));
+
+    [runtime_dir.join("hypr"), PathBuf::from("/tmp/hypr")]
+        .into_iter()
+        .map(|dir| dir.join(&signature).join(name))
+        .find(|socket| socket.exists())
+}
+
+// Sends a request like `reload` or `keyword input:kb_layout de` and returns Hyprland's reply
+pub fn send_command(socket: &Path, command: &str) -> io::Result<String> {
+    let mut stream: UnixStream = UnixStream::connect(socket)?;
+    let mut reply: String = String::new();
+
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    stream.write_all(command.as_bytes())?;
+    stream.shutdown(Shutdown::Write)?;
+    stream.read_to_string(&mut reply)?;
+
+    Ok(reply.trim().to_string())
+}
+
+// Hyprland and hyprpaper answer `ok` to successful requests and an error message otherwise
+pub fn send_checked(socket: &Path, command: &str) -> io::Result<()> {
+    match send_command(socket, command)? {
+        reply if reply == "ok" => Ok(()),
+        reply => Err(io::Error::other(format!("`{}` failed: {}", command, reply))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, os::unix::net::UnixListener, process, thread};
+
+    // Answers each connection with the next reply and returns the requests it received
+    fn serve(socket: &Path, replies: &'static [&'static str]) -> thread::JoinHandle<Vec<String>> {
+        let listener: UnixListener = UnixListener::bind(socket).unwrap();
+
+        thread::spawn(move || {
+            replies
+                .iter()
+                .map(|reply| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut request: String = String::new();
+
+                    // Only ends once the client shut down its writing half
+                    stream.read_to_string(&mut request).unwrap();
+                    stream.write_all(reply.as_bytes()).unwrap();
+
+                    request
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn requests_are_framed_by_shutting_down_the_write_half() {
+        let dir: PathBuf = env::temp_dir().join(format!("autoricer-ipc-{}", process::id()));
+        let socket: PathBuf = dir.join(".socket.sock");
+
+        fs::create_dir_all(&dir).unwrap();
+
+        let server: thread::JoinHandle<Vec<String>> = serve(
+            &socket,
+            &["ok", "ok\n", "config option <kb_layout> does not exist\n"],
+        );
+
+        assert_eq!(
+            send_command(&socket, "keyword input:kb_layout de").unwrap(),
+            "ok"
+        );
+        assert!(send_checked(&socket, "reload").is_ok());
+
+        let error: io::Error = send_checked(&socket, "keyword input:kb_layout xx").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "`keyword input:kb_layout xx` failed: config option <kb_layout> does not exist"
+        );
+        assert_eq!(
+            server.join().unwrap(),
+            [
+                "keyword input:kb_layout de",
+                "reload",
+                "keyword input:kb_layout xx"
+            ]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{
+    hypr::{config::Assignment, includes::ConfigSet},
+    utils::contents::get_migrations,
+    Migration, MigrationAction, Rewrite, Version,
+};
+use std::io;
+
+// Upgrades every file of the config to the syntax of the given Hyprland version
+pub fn migrate_config(configs: &mut ConfigSet, version: Version) -> io::Result<Vec<Rewrite>> {
+    let mut rewrites: Vec<Rewrite> = Vec::new();
+
+    for migration in get_migrations()
+        .into_iter()
+        .filter(|migration| migration.since <= version)
+    {
+        configs.edit_each(|path, config| {
+            let mut modified: bool = false;
+
+            // Lines are rewritten one at a time, since moving a key to another category shifts the line numbers
+            while let Some((assignment, replacement)) = config
+                .assignments()
+                .into_iter()
+                .filter(|assignment| assignment.key == migration.key)
+                .find_map(|assignment| {
+                    let replacement: Option<(&str, String)> = apply(&migration, &assignment)?;
+                    Some((assignment, replacement))
+                })
+            {
+                let after: Option<String> = match replacement {
+                    Some((key, value)) => {
+                        config.rename_line(assignment.line, key, &value)?;
+                        Some(format!("{} = {}", key, value))
+                    }
+                    None => {
+                        config.remove_line(assignment.line)?;
+                        None
+                    }
+                };
+
+                rewrites.push(Rewrite {
+                    since: migration.since,
+                    file: path.to_path_buf(),
+                    before: format!("{} = {}", assignment.key, assignment.value),
+                    after,
+                });
+                modified = true;
+            }
+
+            Ok(modified)
+        })?;
+    }
+
+    Ok(rewrites)
+}
+
+// The new key and value of a line, `Some(None)` if it has to be removed and `None` if it's already up to date
+fn apply<'a>(
+    migration: &'a Migration,
+    assignment: &Assignment,
+) -> Option<Option<(&'a str, String)>> {
+    let replacement: Option<(&str, String)> = match &migration.action {
+        MigrationAction::Rename(key) => Some((key, assignment.value.clone())),
+        MigrationAction::Convert(key, convert) => {
+            convert(&assignment.value).map(|value| (*key, value))
+        }
+        MigrationAction::Remove => None,
+    };
+
+    match &replacement {
+        Some((key, value)) if *key == assignment.key && *value == assignment.value => None,
+        _ => Some(replacement),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs, path::PathBuf, process};
+
+    #[test]
+    fn migrated_window_rules_are_left_unchanged() {
+        let dir: PathBuf = env::temp_dir().join(format!("autoricer-migrate-{}", process::id()));
+        let config: PathBuf = dir.join("hyprland.conf");
+        let migrated: &str = "\
+windowrule = float, class:^(kitty)$
+windowrule = float, group:set
+windowrule = idleinhibit fullscreen, fullscreenstate:* 2
+windowrule = immediate, content:game
+windowrule = float, xdgtag:dialog
+windowrule = center, modal:1
+windowrule = float, title:^(Picture-in-Picture)$
+";
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            &config,
+            format!(
+                "{}windowrule = float, ^(pavucontrol)$\nwindowrulev2 = pin, initialTitle:^(mpv)$\n",
+                migrated
+            ),
+        )
+        .unwrap();
+
+        let mut configs: ConfigSet = ConfigSet::load(&config, &dir).unwrap();
+        let rewrites: Vec<Rewrite> = migrate_config(&mut configs, Version::new(0, 48, 0)).unwrap();
+
+        assert_eq!(
+            rewrites
+                .iter()
+                .map(|rewrite| rewrite.after.as_deref().unwrap_or_default())
+                .collect::<Vec<&str>>(),
+            [
+                "windowrule = float, class:^(pavucontrol)$",
+                "windowrule = pin, initialTitle:^(mpv)$",
+            ]
+        );
+
+        configs.save().unwrap();
+
+        // Running the migration on its own output changes nothing
+        let mut configs: ConfigSet = ConfigSet::load(&config, &dir).unwrap();
+
+        assert!(migrate_config(&mut configs, Version::new(0, 48, 0))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            fs::read_to_string(&config).unwrap(),
+            format!(
+                "{}windowrule = float, class:^(pavucontrol)$\nwindowrule = pin, initialTitle:^(mpv)$\n",
+                migrated
+            )
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
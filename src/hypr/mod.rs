@@ -0,0 +1,10 @@
+pub mod autostart;
+pub mod binds;
+pub mod config;
+pub mod env;
+pub mod idle;
+pub mod includes;
+pub mod ipc;
+pub mod migrate;
+pub mod validate;
+pub mod wallpaper;
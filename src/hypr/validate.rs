@@ -0,0 +1,284 @@
+use crate::{
+    hypr::{config::expand_variables, env::env_name, includes::ConfigSet},
+    keyboard::validate_keyboard_config,
+    utils::contents::get_hypr_option_types,
+    ConfigIssue, HyprOptionType, KeyboardConfig, Severity, XkbRules,
+};
+use std::{collections::BTreeMap, path::Path};
+
+// Keywords which may appear any number of times
+const REPEATABLE_KEYWORDS: &[&str] = &[
+    "source",
+    "env",
+    "exec",
+    "exec-once",
+    "monitor",
+    "workspace",
+    "windowrule",
+    "windowrulev2",
+    "layerrule",
+    "bezier",
+    "animation",
+    "submap",
+    "plugin",
+    "gesture",
+];
+
+// Checks the config Hyprland will load for invalid values, malformed lines and duplicate definitions
+pub fn validate_config(configs: &ConfigSet, rules: &XkbRules) -> Vec<ConfigIssue> {
+    let mut issues: Vec<ConfigIssue> = Vec::new();
+    let option_types: BTreeMap<&str, HyprOptionType> =
+        get_hypr_option_types().into_iter().collect();
+    let mut defined: BTreeMap<String, &Path> = BTreeMap::new();
+    let mut monitors: BTreeMap<String, &Path> = BTreeMap::new();
+    let variables: BTreeMap<String, String> = configs.variables();
+
+    // Hyprland only logs missing sources and keeps loading the rest
+    for path in &configs.missing {
+        issues.push(issue(
+            Severity::Warning,
+            path,
+            None,
+            "sourced file does not exist".to_string(),
+        ));
+    }
+
+    for (path, assignment) in configs.assignments() {
+        let key: &str = assignment.key.as_str();
+        // Hyprland expands variables before it reads the value
+        let value: String = expand_variables(&assignment.value, &variables);
+        let value: &str = value.as_str();
+        let line: Option<usize> = Some(assignment.line + 1);
+
+        if let Some(option_type) = option_types.get(key) {
+            if !option_type.accepts(value) {
+                issues.push(issue(
+                    Severity::Error,
+                    path,
+                    line,
+                    format!("`{}` expects {}, got `{}`", key, option_type, value),
+                ));
+            }
+        }
+
+        match key {
+            "env" if !is_valid_env(value) => issues.push(issue(
+                Severity::Error,
+                path,
+                line,
+                format!("`env = {}` has to look like `env = NAME,value`", value),
+            )),
+            "monitor" => {
+                if let Err(message) = check_monitor(value) {
+                    issues.push(issue(Severity::Error, path, line, message));
+                }
+
+                let name: String = value
+                    .split(',')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string();
+
+                if monitors.insert(name.clone(), path).is_some() {
+                    issues.push(issue(
+                        Severity::Warning,
+                        path,
+                        line,
+                        format!("monitor `{}` is configured more than once", name),
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        let repeatable: bool = key.starts_with('$')
+            || key.split(':').next_back().is_some_and(|name| {
+                REPEATABLE_KEYWORDS.contains(&name) || name.starts_with("bind") || name == "name"
+            });
+
+        if !repeatable && defined.insert(key.to_string(), path).is_some() {
+            issues.push(issue(
+                Severity::Warning,
+                path,
+                line,
+                format!(
+                    "`{}` is defined more than once, only the last definition is used",
+                    key
+                ),
+            ));
+        }
+    }
+
+    if let Some(layout) = configs.get("input:kb_layout") {
+        let setting =
+            |key: &str| expand_variables(&configs.get(key).unwrap_or_default(), &variables);
+        let keyboard: KeyboardConfig = KeyboardConfig::from_settings(
+            &expand_variables(&layout, &variables),
+            &setting("input:kb_variant"),
+            &setting("input:kb_model"),
+            &setting("input:kb_options"),
+        );
+        let path: &Path = configs.paths().next().unwrap_or(Path::new("hyprland.conf"));
+
+        if let Err(message) = validate_keyboard_config(&keyboard, rules) {
+            issues.push(issue(
+                Severity::Error,
+                path,
+                None,
+                format!("invalid keyboard configuration: {}", message),
+            ));
+        } else if keyboard.layouts.len() > 1
+            && !keyboard
+                .options
+                .iter()
+                .any(|option| option.starts_with("grp:"))
+        {
+            issues.push(issue(
+                Severity::Warning,
+                path,
+                None,
+                "no layout switch key (grp:...) is set, switch layouts with `switchxkblayout` binds"
+                    .to_string(),
+            ));
+        }
+    }
+
+    issues
+}
+
+fn issue(severity: Severity, path: &Path, line: Option<usize>, message: String) -> ConfigIssue {
+    ConfigIssue {
+        severity,
+        file: path.to_path_buf(),
+        line,
+        message,
+    }
+}
+
+fn is_valid_env(value: &str) -> bool {
+    let name: &str = env_name(value);
+
+    value.contains(',')
+        && name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// `name,resolution,position,scale[,extra options]` or `name,disable`
+fn check_monitor(value: &str) -> Result<(), String> {
+    let fields: Vec<&str> = value.split(',').map(str::trim).collect();
+
+    if fields.get(1) == Some(&"disable") || fields.get(1) == Some(&"addreserved") {
+        return Ok(());
+    }
+
+    if fields.len() < 4 {
+        return Err(format!(
+            "`monitor = {}` needs a name, resolution, position and scale",
+            value
+        ));
+    }
+
+    let resolution: &str = fields[1];
+    let position: &str = fields[2];
+    let scale: &str = fields[3];
+
+    let valid_resolution: bool =
+        matches!(resolution, "preferred" | "highres" | "highrr" | "maxwidth")
+            || resolution
+                .split_once('@')
+                .map_or(Some(resolution), |(size, rate)| {
+                    rate.parse::<f64>().ok().map(|_| size)
+                })
+                .and_then(|size| size.split_once('x'))
+                .is_some_and(|(w, h)| w.parse::<u32>().is_ok() && h.parse::<u32>().is_ok());
+
+    if !valid_resolution {
+        return Err(format!("invalid monitor resolution `{}`", resolution));
+    }
+
+    let valid_position: bool = position.starts_with("auto")
+        || position
+            .split_once('x')
+            .is_some_and(|(x, y)| x.parse::<i32>().is_ok() && y.parse::<i32>().is_ok());
+
+    if !valid_position {
+        return Err(format!("invalid monitor position `{}`", position));
+    }
+
+    if scale != "auto" && !scale.parse::<f64>().is_ok_and(|scale| scale > 0.0) {
+        return Err(format!("invalid monitor scale `{}`", scale));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keyboard::xkb::parse_xkb_rules;
+    use std::{env, fs, path::PathBuf, process};
+
+    fn validate(name: &str, contents: &str) -> Vec<ConfigIssue> {
+        let dir: PathBuf =
+            env::temp_dir().join(format!("autoricer-validate-{}-{}", name, process::id()));
+        let rules: XkbRules = parse_xkb_rules(include_str!("../../tests/fixtures/evdev.xml"));
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("hyprland.conf"), contents).unwrap();
+
+        let configs: ConfigSet = ConfigSet::load(&dir.join("hyprland.conf"), &dir).unwrap();
+        let issues: Vec<ConfigIssue> = validate_config(&configs, &rules);
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        issues
+    }
+
+    fn errors(issues: &[ConfigIssue]) -> Vec<&str> {
+        issues
+            .iter()
+            .filter(|issue| matches!(issue.severity, Severity::Error))
+            .map(|issue| issue.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn values_are_checked_after_expanding_variables() {
+        let issues: Vec<ConfigIssue> = validate(
+            "variables",
+            "$gaps = 5\n$border = thick\ngeneral {\n    gaps_in = $gaps\n    border_size = $border\n}\n",
+        );
+
+        assert_eq!(
+            errors(&issues),
+            ["`general:border_size` expects an integer, got `thick`"]
+        );
+    }
+
+    #[test]
+    fn gaps_accept_one_to_four_integers() {
+        let issues: Vec<ConfigIssue> = validate(
+            "gaps",
+            "general {\n    gaps_in = 5\n    gaps_out = 5,10,5,10\n}\ngeneral:gaps_out = 1, 2, 3, 4, 5\n",
+        );
+
+        assert_eq!(
+            errors(&issues),
+            ["`general:gaps_out` expects one to four integers, got `1, 2, 3, 4, 5`"]
+        );
+    }
+
+    #[test]
+    fn layouts_without_switch_key_only_warn() {
+        let issues: Vec<ConfigIssue> = validate("layouts", "input {\n    kb_layout = us,de\n}\n");
+
+        assert!(errors(&issues).is_empty());
+        assert!(issues
+            .iter()
+            .any(|issue| issue.message.starts_with("no layout switch key")));
+    }
+}
@@ -0,0 +1,65 @@
+use crate::hypr::config::HyprlandConfig;
+use std::io;
+
+// Points the wallpaper of a monitor, or of every configured monitor, to a new image and preloads only images still in use
+pub fn set_wallpaper(
+    config: &mut HyprlandConfig,
+    image: &str,
+    monitor: Option<&str>,
+) -> io::Result<()> {
+    let wallpapers = config
+        .assignments()
+        .into_iter()
+        .filter(|assignment| assignment.key == "wallpaper");
+
+    // `wallpaper = <monitor>,<path>`, where an empty monitor means all monitors
+    let lines: Vec<(usize, String)> = wallpapers
+        .map(|assignment| {
+            let (name, _) = assignment.value.split_once(',').unwrap_or_default();
+            (assignment.line, name.trim().to_string())
+        })
+        .filter(|(_, name)| monitor.is_none_or(|monitor| monitor == name))
+        .collect();
+
+    if lines.is_empty() {
+        config.push(
+            "wallpaper",
+            &format!("{},{}", monitor.unwrap_or_default(), image),
+        );
+    }
+
+    for (line, name) in lines {
+        config.set_line_value(line, &format!("{},{}", name, image));
+    }
+
+    // Added before the unused ones are removed, so it lands next to the existing preloads
+    if !config
+        .get_all("preload")
+        .iter()
+        .any(|preload| preload == image)
+    {
+        config.push("preload", image);
+    }
+
+    let used: Vec<String> = config
+        .get_all("wallpaper")
+        .into_iter()
+        .filter_map(|value| {
+            value
+                .split_once(',')
+                .map(|(_, path)| path.trim().to_string())
+        })
+        .collect();
+
+    // Removing from the bottom keeps the remaining line numbers valid
+    for preload in config
+        .assignments()
+        .into_iter()
+        .filter(|assignment| assignment.key == "preload" && !used.contains(&assignment.value))
+        .rev()
+    {
+        config.remove_line(preload.line)?;
+    }
+
+    Ok(())
+}
@@ -1,16 +1,44 @@
 use crate::{
-    clone, error, info, prompt, success, tip,
-    utils::{helper_functions::*, types::*},
+    clone, error,
+    hardware::{
+        drm::{detect_drm_cards, is_hybrid},
+        gpu::detect_gpus,
+        input::detect_input_devices,
+        laptop::detect_laptop,
+        monitors::{detect_monitors, hidpi_scale},
+    },
+    hypr::{
+        binds::{compare_binds, parse_binds, set_binds_enabled},
+        config::HyprlandConfig,
+        env::{disable_env_group, enable_env_group, set_env},
+        idle::set_idle_timeouts,
+        includes::ConfigSet,
+        ipc::{hyprland_socket, send_checked, send_command},
+        migrate::migrate_config,
+        validate::validate_config,
+    },
+    info,
+    keyboard::{get_keyboard_config, validate_keyboard_config},
+    prompt, success, tip,
+    utils::{
+        contents::{
+            get_app_variables, get_brightness_keys, get_dependencies, get_env_groups,
+            get_laptop_waybar_modules, get_programs, get_yazi_packages,
+        },
+        helper_functions::*,
+        pacman::{find_package, installed_packages, missing_packages},
+        types::*,
+    },
     warning,
+    waybar::{add_waybar_modules, remove_waybar_modules, waybar_config},
 };
 use colored::Colorize;
-use once_cell::sync::Lazy;
-use regex::Regex;
 use std::{
-    fs::{self, File},
-    io::{self, BufRead, BufReader, BufWriter, Write},
+    fs::{self, DirEntry},
+    io,
+    os::unix::fs::symlink,
     path::{Path, PathBuf},
-    process::Command,
+    process::{Command, ExitStatus},
 };
 
 pub fn installation_prompt() -> io::Result<Installation> {
@@ -33,6 +61,117 @@ pub fn installation_prompt() -> io::Result<Installation> {
     }
 }
 
+// Checks the pacman database for the packages the dotfiles depend on and returns the missing ones
+pub fn check_dependencies(pacman_db: &Path) -> io::Result<Vec<String>> {
+    info!("Checking dependencies of the dotfiles...");
+
+    let packages: Vec<Package> = match installed_packages(pacman_db) {
+        Ok(packages) => packages,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            warning!("==> Could not find the pacman database. Skipping dependency check");
+            return Ok(Vec::new());
+        }
+        Err(error) => return Err(error),
+    };
+
+    let dependencies: Vec<String> = get_dependencies();
+    let required: Vec<&str> = dependencies.iter().map(String::as_str).collect();
+    let missing: Vec<&str> = missing_packages(&packages, &required);
+
+    for dependency in required {
+        match find_package(&packages, dependency) {
+            Some(package) => println!(
+                "{} {}",
+                format!("==> {:<24}", dependency).green(),
+                package.version.green().bold()
+            ),
+            None => println!(
+                "{} {}",
+                format!("==> {:<24}", dependency).red(),
+                "missing".red().bold()
+            ),
+        }
+    }
+
+    if !missing.is_empty() {
+        warning!("==> The following packages are missing:");
+        tip!(format!("sudo pacman -S {}", missing.join(" ")));
+    }
+
+    Ok(missing.into_iter().map(String::from).collect())
+}
+
+// Offers to install missing packages with paru, yay or pacman
+pub fn install_missing_packages(missing: &[String], noconfirm: bool) -> io::Result<PackageInstall> {
+    if missing.is_empty() {
+        return Ok(PackageInstall::Skipped);
+    }
+
+    let Some((helper, helper_path)) = detect_package_helper() else {
+        warning!("==> Could not find pacman or an AUR helper. Please install the missing packages manually");
+        return Ok(PackageInstall::Skipped);
+    };
+
+    loop {
+        prompt!(format!(
+            "Do you want to install the missing packages with {}? [y/N]",
+            helper
+        ));
+
+        let input: String = read_input()?;
+
+        match parse_input(&input) {
+            UserInput::Yes => break,
+            UserInput::No => return Ok(PackageInstall::Skipped),
+            UserInput::Other => prompt!("==> Please enter [y]es or [n]o!"),
+        }
+    }
+
+    info!(format!(
+        "Installing {} with {}...",
+        missing.join(" "),
+        helper
+    ));
+
+    run_package_helper(helper, &helper_path, missing, noconfirm)?;
+
+    Ok(PackageInstall::Installed)
+}
+
+// Runs `<helper> -S --needed <packages>` and fails if the helper doesn't exit successfully
+pub fn run_package_helper(
+    helper: &str,
+    helper_path: &Path,
+    packages: &[String],
+    noconfirm: bool,
+) -> io::Result<()> {
+    // AUR helpers call sudo themselves, pacman has to be run as root
+    let mut command: Command = if helper == "pacman" {
+        let mut sudo: Command = Command::new("sudo");
+        sudo.arg(helper_path);
+        sudo
+    } else {
+        Command::new(helper_path)
+    };
+
+    command.arg("-S").arg("--needed");
+
+    if noconfirm {
+        command.arg("--noconfirm");
+    }
+
+    let status: ExitStatus = command.args(packages).status()?;
+
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "{} exited with {}",
+            helper, status
+        )));
+    }
+
+    Ok(())
+}
+
 // Clones Github repo into ~/Downloads/arch-everforest
 pub fn clone_repo(config_path: &Path, repo_path: &Path) -> io::Result<DownloadStatus> {
     const URL: &str = "https://github.com/3rfaan/dotfiles";
@@ -53,7 +192,7 @@ pub fn clone_repo(config_path: &Path, repo_path: &Path) -> io::Result<DownloadSt
 }
 
 // Delete directories and files which are not needed to moved to ~/.config directory
-pub fn cleanup_repo(paths: &Paths) -> io::Result<()> {
+pub fn cleanup_repo(paths: &Paths, skipped: &[String]) -> io::Result<()> {
     let entries_to_delete: &[&str] = &[
         "arch_wallpaper.jpg",
         "preview_1.png",
@@ -67,7 +206,7 @@ pub fn cleanup_repo(paths: &Paths) -> io::Result<()> {
 
     info!("Removing some directories and files which are not needed to be moved to ~/.config...");
 
-    cleanup(paths, entries_to_delete)?;
+    cleanup(paths, entries_to_delete, skipped)?;
 
     Ok(())
 }
@@ -106,58 +245,518 @@ pub fn create_backup(config_path: &Path, documents_path: &Path) -> io::Result<Ba
     Ok(BackupStatus::Created)
 }
 
+// Checks which programs of the dotfiles are installed and returns the config directories to skip
+pub fn select_config_dirs(paths: &Paths) -> io::Result<Vec<String>> {
+    let mut missing: Vec<&str> = Vec::new();
+
+    info!("Checking which programs are installed...");
+
+    // Without a pacman database programs are only found through $PATH
+    let packages: Vec<Package> = installed_packages(&paths.pacman_db).unwrap_or_default();
+
+    for program in get_programs() {
+        if !paths.repo.join(program.config_dir).exists() {
+            continue;
+        }
+
+        match program_status(&program, &packages) {
+            ProgramStatus::Installed(Some(executable)) => println!(
+                "{} {} {}",
+                format!("==> {:<10}", program.config_dir).green(),
+                format!("{} -> {}", program.name, executable.path.display())
+                    .green()
+                    .bold(),
+                executable.version.unwrap_or_default().bright_black()
+            ),
+            ProgramStatus::Installed(None) => println!(
+                "{} {}",
+                format!("==> {:<10}", program.config_dir).green(),
+                format!("{} (installed with pacman)", program.package)
+                    .green()
+                    .bold()
+            ),
+            ProgramStatus::NotInstalled => {
+                println!(
+                    "{} {}",
+                    format!("==> {:<10}", program.config_dir).red(),
+                    "program not installed".red().bold()
+                );
+                missing.push(program.config_dir);
+            }
+        }
+    }
+
+    if missing.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    warning!("==> Config directories of programs which are not installed will be skipped");
+
+    loop {
+        prompt!("Do you want to copy them anyway? [y/N]");
+
+        let input: String = read_input()?;
+
+        match parse_input(&input) {
+            UserInput::Yes => return Ok(Vec::new()),
+            UserInput::No => break,
+            UserInput::Other => prompt!("==> Please enter [y]es or [n]o!"),
+        }
+    }
+
+    Ok(missing.into_iter().map(String::from).collect())
+}
+
 // Copy directories from ~/Downloads/arch-everforest to ~/.config recursively
-pub fn copy_config_dirs_recursively(src: &Path, dest: &Path) -> io::Result<()> {
+pub fn copy_config_dirs_recursively(src: &Path, dest: &Path, skipped: &[String]) -> io::Result<()> {
     fs::create_dir_all(dest)?;
 
     info!("Copying directories from ~/Downloads/arch-everforest to ~/.config...");
 
-    copy_recursively(src, dest)?;
+    for entry in fs::read_dir(src)? {
+        let entry: DirEntry = entry?;
+
+        if skipped.iter().any(|dir| entry.file_name() == dir.as_str()) {
+            println!(
+                "{} {}",
+                "==> Skipped:".yellow(),
+                entry.file_name().to_string_lossy().yellow().bold()
+            );
+            continue;
+        }
+
+        if entry.file_type()?.is_dir() {
+            fs::create_dir_all(dest.join(entry.file_name()))?;
+            copy_recursively(entry.path(), dest.join(entry.file_name()))?;
+        } else {
+            fs::copy(entry.path(), dest.join(entry.file_name()))?;
+        }
+    }
 
     Ok(())
 }
 
+// Compares the keybindings of the existing Hypr config with the new ones and returns the user's binds to keep
+pub fn check_bind_conflicts(paths: &Paths, skipped: &[String]) -> io::Result<Vec<Bind>> {
+    let hypr_dir: &Path = paths.hypr_config.parent().unwrap_or(&paths.config);
+    let new_config: PathBuf = paths.repo.join("hypr").join("hyprland.conf");
+
+    if skipped.iter().any(|dir| dir == "hypr")
+        || !paths.hypr_config.exists()
+        || !new_config.exists()
+    {
+        return Ok(Vec::new());
+    }
+
+    info!("Comparing your keybindings with the new ones...");
+
+    let old_binds: Vec<Bind> = parse_binds(&ConfigSet::load(&paths.hypr_config, &paths.home)?);
+    let new_binds: Vec<Bind> =
+        parse_binds(&ConfigSet::load_staged(&new_config, &paths.home, hypr_dir)?);
+    let conflicts: BindConflicts = compare_binds(&old_binds, &new_binds);
+
+    for (old, new) in &conflicts.changed {
+        println!(
+            "{} {} {} {} {}",
+            "==> Changed:".yellow(),
+            new.combo().yellow().bold(),
+            old.action().red(),
+            "->".yellow(),
+            new.action().green()
+        );
+    }
+
+    for (first, second) in &conflicts.collisions {
+        println!(
+            "{} {} {} {} {}",
+            "==> Bound twice:".yellow(),
+            first.combo().yellow().bold(),
+            first.action(),
+            "and".yellow(),
+            second.action()
+        );
+    }
+
+    if conflicts.changed.is_empty() && conflicts.collisions.is_empty() {
+        success!("==> None of your keybindings change their meaning");
+    }
+
+    if conflicts.user_only.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    info!("These keybindings only exist in your current config:");
+
+    for bind in &conflicts.user_only {
+        println!(
+            "{} {} {}",
+            "==>".green(),
+            bind.combo().green().bold(),
+            bind.action()
+        );
+    }
+
+    loop {
+        prompt!("Do you want to keep them in ~/.config/hypr/user-binds.conf? [Y/n]");
+
+        let input: String = read_input()?;
+
+        let answer: UserInput = match input.as_str() {
+            "" => UserInput::Yes,
+            _ => parse_input(&input),
+        };
+
+        match answer {
+            UserInput::Yes => return Ok(conflicts.user_only),
+            UserInput::No => return Ok(Vec::new()),
+            UserInput::Other => prompt!("==> Please enter [y]es or [n]o!"),
+        }
+    }
+}
+
+// Writes the kept keybindings into user-binds.conf and sources it from the new Hypr config
+pub fn carry_over_binds(paths: &Paths, binds: &[Bind]) -> io::Result<()> {
+    let hypr_dir: &Path = paths.hypr_config.parent().unwrap_or(&paths.config);
+    let mut contents: String =
+        String::from("# Keybindings carried over from your previous Hyprland config\n");
+    let mut submap: Option<&String> = None;
+
+    for bind in binds {
+        if bind.submap.as_ref() != submap {
+            if submap.is_some() {
+                contents.push_str("submap = reset\n");
+            }
+
+            if let Some(name) = &bind.submap {
+                contents.push_str(&format!("\nsubmap = {}\n", name));
+            }
+
+            submap = bind.submap.as_ref();
+        }
+
+        contents.push_str(&bind.to_line());
+        contents.push('\n');
+    }
+
+    if submap.is_some() {
+        contents.push_str("submap = reset\n");
+    }
+
+    let user_binds: PathBuf = hypr_dir.join("user-binds.conf");
+
+    fs::write(&user_binds, contents)?;
+
+    let mut configs: ConfigSet = ConfigSet::load(&paths.hypr_config, &paths.home)?;
+    let source: String = user_binds.display().to_string();
+
+    if !configs.get_all("source").contains(&source) {
+        configs.push("source", &source);
+        configs.save()?;
+    }
+
+    Ok(())
+}
+
+// Prompt for the main modifier and the applications started by keybindings
+pub fn change_default_apps(paths: &Paths, answers: &Answers) -> io::Result<HyprConfig> {
+    let mut configs: ConfigSet = ConfigSet::load(&paths.hypr_config, &paths.home)?;
+    let main_mod: Option<String> = configs.get("$mainMod");
+    let variables: Vec<AppVariable> = get_app_variables();
+
+    // With any of these keys in the answers file, nothing of this step is asked and the rest stays as it is
+    let answered: bool = answers.get("mainMod").is_some()
+        || variables
+            .iter()
+            .any(|variable| answers.get(variable.name).is_some());
+
+    if !answered {
+        loop {
+            prompt!("Do you want to change your main modifier key or default applications? [y/N]");
+
+            match parse_input(&read_input()?) {
+                UserInput::Yes => break,
+                UserInput::No => return Ok(HyprConfig::Default),
+                UserInput::Other => prompt!("==> Please enter [y]es or [n]o!"),
+            }
+        }
+    }
+
+    if let Some(main_mod) = &main_mod {
+        let new_mod: Option<&str> = match answers.get("mainMod") {
+            Some(answer) => {
+                let new_mod: Option<&str> = parse_main_mod(answer);
+
+                if new_mod.is_none() && !answer.is_empty() {
+                    warning!(format!(
+                        "==> Ignoring mainMod={}, expected SUPER or ALT",
+                        answer
+                    ));
+                }
+
+                new_mod
+            }
+            None if answered => None,
+            None => prompt_main_mod(main_mod)?,
+        };
+
+        if let Some(new_mod) = new_mod.filter(|new_mod| new_mod != main_mod) {
+            let file: PathBuf = configs.set("$mainMod", new_mod).to_path_buf();
+            print_changed_setting(&file, &format!("$mainMod = {}", new_mod));
+        }
+    }
+
+    for variable in &variables {
+        let key: String = format!("${}", variable.name);
+
+        // Only variables the dotfiles actually use are worth asking for
+        let Some(current) = configs.get(&key) else {
+            continue;
+        };
+
+        let command: Option<String> = match answers.get(variable.name) {
+            Some("") => None,
+            Some(answer) => {
+                let program: &str = answer.split_whitespace().next().unwrap_or_default();
+
+                match find_executable(program) {
+                    Some(_) => Some(answer.to_string()),
+                    None => {
+                        warning!(format!(
+                            "==> Ignoring {}={}, could not find {} in your $PATH",
+                            variable.name, answer, program
+                        ));
+                        None
+                    }
+                }
+            }
+            None if answered => None,
+            None => prompt_app_command(variable, &current)?,
+        };
+
+        if let Some(command) = command.filter(|command| *command != current) {
+            let file: PathBuf = configs.set(&key, &command).to_path_buf();
+            print_changed_setting(&file, &format!("{} = {}", key, command));
+        }
+    }
+
+    // Changing the modifier can make two binds share the same keys
+    for (first, second) in compare_binds(&[], &parse_binds(&configs)).collisions {
+        println!(
+            "{} {} {} {} {}",
+            "==> Bound twice:".yellow(),
+            first.combo().yellow().bold(),
+            first.action(),
+            "and".yellow(),
+            second.action()
+        );
+    }
+
+    match configs.save()?.is_empty() {
+        true => Ok(HyprConfig::Default),
+        false => Ok(HyprConfig::Modified),
+    }
+}
+
+fn parse_main_mod(input: &str) -> Option<&'static str> {
+    match input.to_lowercase().as_str() {
+        "s" | "super" => Some("SUPER"),
+        "a" | "alt" => Some("ALT"),
+        _ => None,
+    }
+}
+
+// Returns the new modifier, or `None` to keep the current one
+fn prompt_main_mod(current: &str) -> io::Result<Option<&'static str>> {
+    loop {
+        prompt!(format!(
+            "Main modifier: [s]uper or [a]lt? Press Enter to keep [{}]",
+            current
+        ));
+
+        let input: String = read_input()?;
+
+        if input.is_empty() {
+            return Ok(None);
+        }
+
+        match parse_main_mod(&input) {
+            Some(new_mod) => return Ok(Some(new_mod)),
+            None => prompt!("==> Please enter [s]uper or [a]lt!"),
+        }
+    }
+}
+
+// Returns the new command, or `None` to keep the current one
+fn prompt_app_command(variable: &AppVariable, current: &str) -> io::Result<Option<String>> {
+    let installed: Vec<&str> = variable
+        .candidates
+        .iter()
+        .copied()
+        .filter(|candidate| {
+            candidate
+                .split_whitespace()
+                .next()
+                .is_some_and(|program| find_executable(program).is_some())
+        })
+        .collect();
+
+    if !installed.is_empty() {
+        tip!(format!("Installed: {}", installed.join(", ")));
+    }
+
+    loop {
+        prompt!(format!(
+            "{} is set to [{}]. Enter a different command or press Enter to keep it:",
+            variable.description, current
+        ));
+
+        let command: String = read_raw_input()?;
+
+        if command.is_empty() {
+            return Ok(None);
+        }
+
+        let program: &str = command.split_whitespace().next().unwrap_or_default();
+
+        match find_executable(program) {
+            Some(_) => return Ok(Some(command)),
+            None => warning!(format!("==> Could not find {} in your $PATH", program)),
+        }
+    }
+}
+
+// Rewrites options the installed Hyprland renamed or removed since the dotfiles were written
+pub fn migrate_hypr_config(paths: &Paths) -> io::Result<HyprConfig> {
+    let Some(version) = lookup_executable("Hyprland", Some("--version"))
+        .and_then(|executable| executable.version)
+        .and_then(|version| Version::parse(&version))
+    else {
+        warning!("==> Could not detect the installed Hyprland version, skipping config migration");
+        return Ok(HyprConfig::Default);
+    };
+
+    info!(format!("Migrating Hypr config to Hyprland {}...", version));
+
+    let mut configs: ConfigSet = ConfigSet::load(&paths.hypr_config, &paths.home)?;
+    let rewrites: Vec<Rewrite> = migrate_config(&mut configs, version)?;
+
+    for rewrite in &rewrites {
+        let after: String = match &rewrite.after {
+            Some(after) => after.clone(),
+            None => String::from("(removed)"),
+        };
+
+        println!(
+            "{} {} {} {}",
+            format!("==> [{}] {}:", rewrite.since, rewrite.file.display()).green(),
+            rewrite.before.red(),
+            "->".green(),
+            after.green().bold(),
+        );
+    }
+
+    match configs.save()?.is_empty() {
+        true => Ok(HyprConfig::Default),
+        false => Ok(HyprConfig::Modified),
+    }
+}
+
 // Prompt for changing settings inside ~/.config/hypr/hyprland.conf
-pub fn change_settings(hypr_config: &Path) -> io::Result<HyprConfig> {
+pub fn change_settings(
+    paths: &Paths,
+    rules: &XkbRules,
+    detected_keyboard: Option<DetectedKeyboard>,
+) -> io::Result<HyprConfig> {
     let mut input: String;
 
-    let mut change_kb_layout: bool;
-    let mut layout_code: String = String::from("us");
+    let mut keyboard: Option<KeyboardConfig> = None;
 
     let change_nvidia_env_vars: bool;
 
-    loop {
-        prompt!("Keyboard layout is currently set to [us]. Would you like to change it? [y/N]");
+    let current_layout: String = ConfigSet::load(&paths.hypr_config, &paths.home)?
+        .get("input:kb_layout")
+        .unwrap_or_else(|| String::from("us"));
+
+    // A layout detected on the system is offered as the default, so it doesn't silently get replaced
+    if let Some(detected) =
+        detected_keyboard.filter(|detected| detected.keyboard.layouts.join(",") != current_layout)
+    {
+        match validate_keyboard_config(&detected.keyboard, rules) {
+            Ok(()) => loop {
+                prompt!(format!(
+                    "Your system uses the keyboard layout [{}] (found in {}). Do you want to keep it? [Y/n]",
+                    detected.keyboard.describe(),
+                    detected.source.display()
+                ));
+
+                input = read_input()?;
+
+                // Keeping the detected layout is the default answer
+                let answer: UserInput = match input.as_str() {
+                    "" => UserInput::Yes,
+                    _ => parse_input(&input),
+                };
+
+                match answer {
+                    UserInput::Yes => {
+                        keyboard = Some(detected.keyboard);
+                        break;
+                    }
+                    UserInput::No => break,
+                    UserInput::Other => prompt!("==> Please enter [y]es or [n]o!"),
+                }
+            },
+            Err(message) => warning!(format!(
+                "==> Ignoring keyboard layout found in {}: {}",
+                detected.source.display(),
+                message
+            )),
+        }
+    }
+
+    while keyboard.is_none() {
+        prompt!(format!(
+            "Keyboard layout is currently set to [{}]. Would you like to change it? [y/N]",
+            current_layout
+        ));
 
         input = read_input()?;
 
         match parse_input(&input) {
-            UserInput::Yes => {
-                change_kb_layout = true;
-                break;
-            }
-            UserInput::No => {
-                change_kb_layout = false;
-                break;
-            }
+            UserInput::Yes => match get_keyboard_config(rules)? {
+                KBLayout::Change(config) => keyboard = Some(config),
+                KBLayout::Default => break,
+            },
+            UserInput::No => break,
             UserInput::Other => prompt!("==> Please enter [y]es or [n]o!"),
         }
     }
 
-    if change_kb_layout {
-        match get_kb_layout_code() {
-            Ok(KBLayout::Change(code)) => layout_code = code,
-            Ok(KBLayout::Default) => change_kb_layout = false,
-            Err(error) => return Err(error),
-        }
+    let gpu_report: GpuReport = detect_gpus(&paths.sys, &paths.proc);
+    let recommended: bool = gpu_report.needs_nvidia_env_vars();
+
+    info!("Detecting graphics cards...");
+
+    for reason in &gpu_report.reasons {
+        tip!(format!("==> {}", reason));
     }
 
     loop {
-        prompt!("Are you using a NVIDIA graphics card? [y/N]");
+        if recommended {
+            prompt!("Are you using a NVIDIA graphics card? [Y/n]");
+        } else {
+            prompt!("Are you using a NVIDIA graphics card? [y/N]");
+        }
 
         input = read_input()?;
 
-        match parse_input(&input) {
+        // The detected answer is the default
+        let answer: UserInput = match input.as_str() {
+            "" if recommended => UserInput::Yes,
+            _ => parse_input(&input),
+        };
+
+        match answer {
             UserInput::Yes => {
                 change_nvidia_env_vars = true;
                 break;
@@ -170,88 +769,528 @@ pub fn change_settings(hypr_config: &Path) -> io::Result<HyprConfig> {
         }
     }
 
-    if !change_kb_layout && !change_nvidia_env_vars {
-        return Ok(HyprConfig::Default);
+    let drm_cards: Vec<DrmCard> = detect_drm_cards(&paths.sys);
+
+    if drm_cards.len() > 1 {
+        info!("Found multiple GPUs, Hyprland will use them in this order:");
+
+        for card in &drm_cards {
+            tip!(format!(
+                "==> {} ({} at {})",
+                card.name, card.vendor, card.pci_address
+            ));
+        }
     }
 
     update_hypr_config(
-        hypr_config,
-        change_kb_layout,
+        &paths.hypr_config,
+        &paths.home,
+        keyboard.as_ref(),
         change_nvidia_env_vars,
-        &layout_code,
-    )?;
-
-    Ok(HyprConfig::Modified)
+        &drm_cards,
+    )
 }
 
 // Helper function for `change_settings()` to modify Hyprland config file
 fn update_hypr_config(
     hypr_config: &Path,
-    change_kb_layout: bool,
+    home_path: &Path,
+    keyboard: Option<&KeyboardConfig>,
     change_nvidia_env_vars: bool,
-    layout_code: &str,
+    drm_cards: &[DrmCard],
 ) -> io::Result<HyprConfig> {
-    if layout_code == "us" && !change_nvidia_env_vars {
+    // The settings may live in any file sourced by hyprland.conf
+    let mut configs: ConfigSet = ConfigSet::load(hypr_config, home_path)?;
+
+    info!("Modifying Hypr config with your settings...");
+
+    if let Some(keyboard) = keyboard {
+        for (key, value) in keyboard.hypr_settings() {
+            // Empty settings are only written to clear a value set by the dotfiles
+            if value.is_empty() && configs.get(key).is_none() {
+                continue;
+            }
+
+            let file: PathBuf = configs.set(key, &value).to_path_buf();
+
+            print_changed_setting(&file, &format!("{} = {}", key, value));
+        }
+    }
+
+    // Hyprland picks the first card as its primary GPU, so the boot display's card goes first
+    if drm_cards.len() > 1 {
+        let hypr_dir: &Path = hypr_config.parent().unwrap_or(Path::new("."));
+        let devices: String = link_drm_cards(hypr_dir, drm_cards)?;
+        let file: PathBuf = set_env(&mut configs, "AQ_DRM_DEVICES", &devices)?;
+
+        print_changed_setting(&file, &format!("env = AQ_DRM_DEVICES,{}", devices));
+    }
+
+    let nvidia: EnvGroup = get_env_groups()
+        .into_iter()
+        .find(|group| group.name == "nvidia")
+        .expect("Could not find NVIDIA environment variables");
+
+    if change_nvidia_env_vars {
+        // With an integrated GPU rendering, GBM and GLX have to stay on its driver
+        let skipped: &[&str] = if is_hybrid(drm_cards) {
+            &["GBM_BACKEND", "__GLX_VENDOR_LIBRARY_NAME"]
+        } else {
+            &[]
+        };
+
+        for (file, assignment) in enable_env_group(&mut configs, &nvidia, skipped)? {
+            print_changed_setting(&file, &format!("env = {}", assignment));
+        }
+    } else {
+        // Variables enabled by an earlier run are removed again
+        for (file, assignment) in disable_env_group(&mut configs, &nvidia)? {
+            println!(
+                "{} {} {}",
+                "==> Removed the following setting from".green(),
+                file.display().to_string().green(),
+                format!("env = {}", assignment).green().bold()
+            );
+        }
+    }
+
+    let saved: Vec<PathBuf> = configs.save()?;
+
+    for path in &saved {
+        println!(
+            "{} {}",
+            "==> Saved Hypr config file:".green(),
+            path.display().to_string().green().bold()
+        );
+    }
+
+    if saved.is_empty() {
         return Ok(HyprConfig::Default);
     }
 
-    // Path to Hyprland config file
-    let hypr_config_file: File = File::open(hypr_config)?;
-    let hypr_config_reader: BufReader<File> = BufReader::new(hypr_config_file);
+    Ok(HyprConfig::Modified)
+}
 
-    let temp_file_path: &Path = Path::new("./hyprland.conf");
-    let temp_file: File = File::create(temp_file_path)?;
-    let mut temp_file_stream: BufWriter<File> = BufWriter::new(temp_file);
+fn print_changed_setting(file: &Path, setting: &str) {
+    println!(
+        "{} {} {}",
+        "==> Changed the following setting in".green(),
+        file.display().to_string().green(),
+        setting.green().bold()
+    );
+}
 
-    let old_layout: &str = "kb_layout = us";
-    let new_layout: String = format!("kb_layout = {}", layout_code);
+// Prompts for touchpad and pointer settings depending on the connected input devices
+pub fn configure_input(paths: &Paths) -> io::Result<HyprConfig> {
+    let devices: Vec<InputDevice> = detect_input_devices(&paths.proc);
+    let mut change_pointer: bool = false;
 
-    static NVIDIA_ENV_VARS_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^#(env = .+)$").unwrap());
+    if devices.is_empty() {
+        return Ok(HyprConfig::Default);
+    }
 
-    info!("Modifying Hypr config with your settings...");
+    let mut configs: ConfigSet = ConfigSet::load(&paths.hypr_config, &paths.home)?;
 
-    for line in hypr_config_reader.lines() {
-        let mut line: String = line?;
+    for device in &devices {
+        let kind: &str = match device.kind {
+            InputDeviceKind::Touchpad => "Touchpad:",
+            InputDeviceKind::Mouse => "Mouse:   ",
+        };
 
-        if change_kb_layout && line.contains(old_layout) {
-            line = line.replace(old_layout, &new_layout);
+        println!(
+            "{} {}",
+            format!("==> {}", kind).green(),
+            device.name.green().bold()
+        );
+    }
 
-            println!(
-                "{} {}",
-                "==> Changed the following line in Hypr config file:".green(),
-                line.trim().green().bold()
-            );
+    if devices
+        .iter()
+        .any(|device| matches!(device.kind, InputDeviceKind::Touchpad))
+    {
+        // Questions together with Hyprland's default for keys the config doesn't set
+        for (key, question, default) in [
+            (
+                "input:touchpad:natural_scroll",
+                "Natural scrolling on your touchpad (content follows your fingers)?",
+                false,
+            ),
+            (
+                "input:touchpad:tap-to-click",
+                "Click by tapping your touchpad?",
+                true,
+            ),
+        ] {
+            let current: bool = configs.get(key).map_or(default, |value| {
+                matches!(value.as_str(), "true" | "yes" | "on" | "1")
+            });
+
+            loop {
+                prompt!(format!(
+                    "{} [y]es or [n]o, press Enter to keep [{}]",
+                    question,
+                    match current {
+                        true => "yes",
+                        false => "no",
+                    }
+                ));
+
+                let input: String = read_input()?;
+
+                let enabled: bool = match parse_input(&input) {
+                    _ if input.is_empty() => break,
+                    UserInput::Yes => true,
+                    UserInput::No => false,
+                    UserInput::Other => {
+                        prompt!("==> Please enter [y]es or [n]o!");
+                        continue;
+                    }
+                };
+
+                if enabled != current {
+                    let value: &str = match enabled {
+                        true => "true",
+                        false => "false",
+                    };
+                    let file: PathBuf = configs.set(key, value).to_path_buf();
+                    print_changed_setting(&file, &format!("{} = {}", key, value));
+                }
+
+                break;
+            }
+        }
+    }
+
+    loop {
+        prompt!("Do you want to change the pointer sensitivity or acceleration? [y/N]");
+
+        match parse_input(&read_input()?) {
+            UserInput::Yes => {
+                change_pointer = true;
+                break;
+            }
+            UserInput::No => break,
+            UserInput::Other => prompt!("==> Please enter [y]es or [n]o!"),
+        }
+    }
+
+    if change_pointer {
+        loop {
+            prompt!(format!(
+                "Sensitivity from -1.0 (slow) to 1.0 (fast), press Enter to keep [{}]:",
+                configs
+                    .get("input:sensitivity")
+                    .unwrap_or_else(|| String::from("0"))
+            ));
+
+            let input: String = read_input()?;
+
+            if input.is_empty() {
+                break;
+            }
+
+            match input.parse::<f64>() {
+                Ok(sensitivity) if (-1.0..=1.0).contains(&sensitivity) => {
+                    let file: PathBuf = configs.set("input:sensitivity", &input).to_path_buf();
+                    print_changed_setting(&file, &format!("input:sensitivity = {}", input));
+                    break;
+                }
+                _ => prompt!("==> Please enter a number between -1.0 and 1.0!"),
+            }
         }
 
-        if change_nvidia_env_vars && NVIDIA_ENV_VARS_RE.is_match(&line) {
-            line = NVIDIA_ENV_VARS_RE.replace(&line, "$1").to_string();
+        loop {
+            prompt!(format!(
+                "Acceleration profile: [a]daptive or [f]lat? Press Enter to keep [{}]",
+                configs
+                    .get("input:accel_profile")
+                    .filter(|profile| !profile.is_empty())
+                    .unwrap_or_else(|| String::from("adaptive"))
+            ));
+
+            let profile: &str = match read_input()?.as_str() {
+                "" => break,
+                "a" | "adaptive" => "adaptive",
+                "f" | "flat" => "flat",
+                _ => {
+                    prompt!("==> Please enter [a]daptive or [f]lat!");
+                    continue;
+                }
+            };
+
+            let file: PathBuf = configs.set("input:accel_profile", profile).to_path_buf();
+            print_changed_setting(&file, &format!("input:accel_profile = {}", profile));
+
+            break;
         }
+    }
+
+    match configs.save()?.is_empty() {
+        true => Ok(HyprConfig::Default),
+        false => Ok(HyprConfig::Modified),
+    }
+}
+
+// Turns the laptop parts of the dotfiles on or off depending on whether the machine has a battery
+pub fn configure_laptop(paths: &Paths, edit_hypr: bool) -> io::Result<HyprConfig> {
+    let report: LaptopReport = detect_laptop(&paths.sys);
+    let laptop: bool = report.is_laptop();
+    let mut modified: bool = false;
+
+    match laptop {
+        true => info!(format!(
+            "Detected a laptop (battery: {}, backlight: {})",
+            report.batteries.join(", "),
+            match report.backlights.is_empty() {
+                true => String::from("none"),
+                false => report.backlights.join(", "),
+            }
+        )),
+        false => info!("No battery found, configuring a desktop"),
+    }
+
+    // Brightness keys need a backlight, which some laptops with OLED or external screens lack
+    let brightness: bool = laptop && !report.backlights.is_empty();
+
+    // The Waybar part below still runs when Hyprland is skipped or has no config
+    if edit_hypr && paths.hypr_config.exists() {
+        let mut configs: ConfigSet = ConfigSet::load(&paths.hypr_config, &paths.home)?;
+
+        for (file, bind) in set_binds_enabled(&mut configs, &get_brightness_keys(), brightness)? {
+            print_changed_setting(&file, &bind);
+            modified = true;
+        }
+
+        configs.save()?;
+    }
+
+    if let Some(waybar) = waybar_config(&paths.config) {
+        // The backlight module needs a backlight, everything else just a battery
+        let (shown, hidden): (Vec<&str>, Vec<&str>) = get_laptop_waybar_modules()
+            .into_iter()
+            .partition(|module| match *module {
+                "backlight" => brightness,
+                _ => laptop,
+            });
+
+        let added: Vec<String> = add_waybar_modules(&waybar, &shown)?;
+        let removed: Vec<String> = remove_waybar_modules(&waybar, &hidden)?;
+
+        for module in &added {
+            print_changed_setting(&waybar, &format!("added module {}", module));
+        }
+
+        for module in &removed {
+            print_changed_setting(&waybar, &format!("removed module {}", module));
+        }
+
+        modified |= !added.is_empty() || !removed.is_empty();
+    }
+
+    let hypridle: PathBuf = paths.hypr_config.with_file_name("hypridle.conf");
+
+    if edit_hypr && hypridle.exists() {
+        let mut config: HyprlandConfig = HyprlandConfig::load(&hypridle)?;
+        let changed: Vec<(String, String)> = set_idle_timeouts(&mut config, laptop);
+
+        for (action, seconds) in &changed {
+            print_changed_setting(&hypridle, &format!("timeout = {} ({})", seconds, action));
+        }
+
+        if !changed.is_empty() {
+            config.save(&hypridle)?;
+            modified = true;
+        }
+    }
+
+    match modified {
+        true => Ok(HyprConfig::Modified),
+        false => Ok(HyprConfig::Default),
+    }
+}
+
+// Proposes `monitor =` lines for all connected outputs and writes them to the Hyprland config on confirmation
+pub fn configure_monitors(paths: &Paths) -> io::Result<HyprConfig> {
+    let monitors: Vec<Monitor> = detect_monitors(&paths.sys);
+
+    if monitors.is_empty() {
+        return Ok(HyprConfig::Default);
+    }
+
+    info!("Detecting monitors...");
+
+    for (number, monitor) in monitors.iter().enumerate() {
+        println!(
+            "{} -> {} {} ({}x{}@{:.2}Hz, {}x{} mm)",
+            number + 1,
+            monitor.name.bold(),
+            monitor.model.as_deref().unwrap_or(&monitor.manufacturer),
+            monitor.width,
+            monitor.height,
+            monitor.refresh_rate,
+            monitor.width_mm,
+            monitor.height_mm
+        );
+    }
 
-        temp_file_stream.write_all(line.as_bytes())?;
-        temp_file_stream.write_all(b"\n")?;
+    let order: Vec<usize> = if monitors.len() > 1 {
+        get_monitor_order(monitors.len())?
+    } else {
+        vec![0]
+    };
+
+    // Monitors are placed next to each other using their scaled width
+    let mut x: u32 = 0;
+    let mut monitor_lines: Vec<(&str, String)> = Vec::new();
+
+    for index in order {
+        let monitor: &Monitor = &monitors[index];
+        let scale: f64 = hidpi_scale(monitor);
+
+        monitor_lines.push((
+            &monitor.name,
+            format!(
+                "{},{}x{}@{:.2},{}x0,{}",
+                monitor.name, monitor.width, monitor.height, monitor.refresh_rate, x, scale
+            ),
+        ));
+
+        x += (monitor.width as f64 / scale) as u32;
     }
 
-    temp_file_stream.flush()?;
+    info!("Proposed monitor configuration:");
+
+    for (_, line) in &monitor_lines {
+        tip!(format!("monitor = {}", line));
+    }
 
-    if temp_file_path.exists() {
-        fs::copy(temp_file_path, hypr_config)?;
-        fs::remove_file(temp_file_path)?;
+    loop {
+        prompt!("Do you want to write these monitor lines to the Hypr config? [y/N]");
 
-        success!("==> Copied new Hypr config file to ~/.config/Hypr/hyprland.conf");
-        success!("==> Removed temporary file");
+        let input: String = read_input()?;
+
+        match parse_input(&input) {
+            UserInput::Yes => break,
+            UserInput::No => return Ok(HyprConfig::Default),
+            UserInput::Other => prompt!("==> Please enter [y]es or [n]o!"),
+        }
+    }
+
+    let mut configs: ConfigSet = ConfigSet::load(&paths.hypr_config, &paths.home)?;
+
+    // The generic `monitor = ,preferred,auto,1` line of the dotfiles stays as fallback for other monitors
+    for (name, line) in monitor_lines {
+        let file: PathBuf = match configs.set_where(
+            "monitor",
+            |value| value.split(',').next().is_some_and(|n| n.trim() == name),
+            &line,
+        ) {
+            Some(file) => file.to_path_buf(),
+            None => configs.push("monitor", &line).to_path_buf(),
+        };
+
+        print_changed_setting(&file, &format!("monitor = {}", line));
     }
 
+    configs.save()?;
+
     Ok(HyprConfig::Modified)
 }
 
-pub fn install_cli_utilities(home_path: &Path, config_path: &Path) -> io::Result<DownloadStatus> {
+fn get_monitor_order(count: usize) -> io::Result<Vec<usize>> {
+    let default: Vec<String> = (1..=count).map(|number| number.to_string()).collect();
+
+    loop {
+        prompt!(format!(
+            "Enter the numbers of your monitors from left to right separated by commas [{}]",
+            default.join(",")
+        ));
+
+        let input: String = read_input()?;
+
+        if input.is_empty() {
+            return Ok((0..count).collect());
+        }
+
+        let order: Vec<usize> = input
+            .split(',')
+            .filter_map(|number| number.trim().parse::<usize>().ok())
+            .filter(|number| (1..=count).contains(number))
+            .map(|number| number - 1)
+            .collect();
+
+        let mut sorted: Vec<usize> = order.clone();
+        sorted.sort();
+        sorted.dedup();
+
+        if order.len() == count && sorted.len() == count {
+            return Ok(order);
+        }
+
+        prompt!(format!(
+            "==> Please enter each number from 1 to {} exactly once!",
+            count
+        ));
+    }
+}
+
+// `AQ_DRM_DEVICES` is colon separated, so the by-path names (which contain colons) are linked into ~/.config/hypr
+fn link_drm_cards(hypr_dir: &Path, drm_cards: &[DrmCard]) -> io::Result<String> {
+    let mut links: Vec<String> = Vec::new();
+
+    for card in drm_cards {
+        let link: PathBuf = hypr_dir.join(format!("{}-{}", card.vendor.short_name(), card.name));
+
+        if link.symlink_metadata().is_ok() {
+            fs::remove_file(&link)?;
+        }
+
+        symlink(&card.by_path, &link)?;
+
+        println!(
+            "{} {} {}",
+            "==> Linked".green(),
+            link.display().to_string().green().bold(),
+            format!("-> {}", card.by_path.display()).green()
+        );
+
+        links.push(link.display().to_string());
+    }
+
+    Ok(links.join(":"))
+}
+
+pub fn install_cli_utilities(
+    home_path: &Path,
+    config_path: &Path,
+    skipped: &[String],
+) -> io::Result<DownloadStatus> {
     let zsh_path: PathBuf = home_path.join(".zsh");
     let yazi_path: PathBuf = config_path.join("yazi/plugins");
+    let install_zsh: bool = !skipped.iter().any(|dir| dir == "zsh");
+    let install_yazi: bool = !skipped.iter().any(|dir| dir == "yazi");
+
+    if !install_zsh && !install_yazi {
+        return Ok(DownloadStatus::Existing);
+    }
 
     info!("Installing CLI utilies");
 
+    if install_zsh {
+        install_zsh_plugins(&zsh_path)?;
+    }
+
+    if install_yazi {
+        install_yazi_packages(&yazi_path)?;
+    }
+
+    Ok(DownloadStatus::Success)
+}
+
+// Clones the plugins sourced by the .zshrc of the dotfiles into ~/.zsh
+fn install_zsh_plugins(zsh_path: &Path) -> io::Result<()> {
     if !zsh_path.exists() {
-        fs::create_dir_all(&zsh_path)?;
+        fs::create_dir_all(zsh_path)?;
     }
 
     if !zsh_path.join("zsh-autosuggestions").exists() {
@@ -272,20 +1311,17 @@ pub fn install_cli_utilities(home_path: &Path, config_path: &Path) -> io::Result
         success!("==> Successfully cloned zsh-syntax-highlighting");
     }
 
+    Ok(())
+}
+
+fn install_yazi_packages(yazi_path: &Path) -> io::Result<()> {
     if !yazi_path.exists() {
-        fs::create_dir_all(&yazi_path)?;
+        fs::create_dir_all(yazi_path)?;
     }
 
-    let yazi_packages: &[&str] = &[
-        "dedukun/bookmarks",
-        "dedukun/relative-motions",
-        "yazi-rs/flavors:catppuccin-macchiato",
-        "yazi-rs/plugins:full-border",
-    ];
-
-    if command_exists("ya") {
-        for &package in yazi_packages {
-            Command::new("ya")
+    if let Some(ya) = find_executable("ya") {
+        for package in get_yazi_packages() {
+            Command::new(&ya)
                 .arg("pack")
                 .arg("-a")
                 .arg(package)
@@ -295,22 +1331,171 @@ pub fn install_cli_utilities(home_path: &Path, config_path: &Path) -> io::Result
         warning!("Could not install ya packages for yazi. Make sure ya is installed and try to install them manually");
     }
 
-    Ok(DownloadStatus::Success)
+    Ok(())
 }
 
-pub fn after_install(repo_path: &Path) -> io::Result<()> {
+// Reloads a running Hyprland over its IPC socket and applies the keyboard settings right away
+pub fn reload_hyprland(paths: &Paths) -> io::Result<ReloadStatus> {
+    let Some(socket) = hyprland_socket() else {
+        return Ok(ReloadStatus::NotRunning);
+    };
+
+    info!("Reloading Hyprland...");
+
+    send_checked(&socket, "reload")?;
+
+    // Hyprland keeps running with a broken config and only reports the problems when asked
+    let errors: Vec<String> = send_command(&socket, "configerrors")?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect();
+
+    if !errors.is_empty() {
+        return Ok(ReloadStatus::ConfigErrors(errors));
+    }
+
+    success!("==> Hyprland reloaded the new config");
+
+    let configs: ConfigSet = ConfigSet::load(&paths.hypr_config, &paths.home)?;
+
+    for key in [
+        "input:kb_layout",
+        "input:kb_variant",
+        "input:kb_model",
+        "input:kb_options",
+    ] {
+        if let Some(value) = configs.get(key) {
+            send_checked(&socket, &format!("keyword {} {}", key, value))?;
+
+            println!(
+                "{} {}",
+                "==> Applied".green(),
+                format!("{} = {}", key, value).green().bold()
+            );
+        }
+    }
+
+    Ok(ReloadStatus::Reloaded)
+}
+
+// Re-reads the written config and refuses it if Hyprland would not accept it
+pub fn validate_hypr_config(paths: &Paths, rules: &XkbRules) -> io::Result<()> {
+    // Nothing to check if the hypr directory was skipped and Hyprland was never set up
+    if !paths.hypr_config.exists() {
+        return Ok(());
+    }
+
+    let configs: ConfigSet = ConfigSet::load(&paths.hypr_config, &paths.home)?;
+    let issues: Vec<ConfigIssue> = validate_config(&configs, rules);
+    let mut errors: usize = 0;
+
+    for issue in &issues {
+        let location: String = match issue.line {
+            Some(line) => format!("{}:{}", issue.file.display(), line),
+            None => issue.file.display().to_string(),
+        };
+
+        match issue.severity {
+            Severity::Error => {
+                errors += 1;
+                println!(
+                    "{} {} {}",
+                    "==> Error in".red(),
+                    location.red().bold(),
+                    issue.message
+                );
+            }
+            Severity::Warning => {
+                println!(
+                    "{} {} {}",
+                    "==> Warning in".yellow(),
+                    location.yellow().bold(),
+                    issue.message
+                );
+            }
+        }
+    }
+
+    match errors {
+        0 => Ok(()),
+        _ => Err(io::Error::other(format!(
+            "The Hypr config contains {} error(s)",
+            errors
+        ))),
+    }
+}
+
+pub fn after_install(paths: &Paths, reload_status: ReloadStatus) -> io::Result<()> {
     info!("Removing ~/Downloads/arch-everforest repo");
 
-    if repo_path.exists() {
-        fs::remove_dir_all(repo_path)?;
+    if paths.repo.exists() {
+        fs::remove_dir_all(&paths.repo)?;
     }
 
     success!("==> Removed repo successfully".green());
 
     info!("Installation succeeded! 🎉");
 
-    tip!("Tip: After this installation you have to restart Hyprland by pressing <SUPER> + <SHIFT> + E");
-    tip!("Tip: To change your wallpaper change the path to another picture inside ~/.config/hypr/hyprpaper.conf");
+    if !matches!(reload_status, ReloadStatus::Reloaded) {
+        tip!("Tip: After this installation you have to restart Hyprland by pressing <SUPER> + <SHIFT> + E");
+    }
+
+    tip!("Tip: Run `autoricer keys` to see all keybindings of the new config");
+    tip!("Tip: To change your wallpaper run `autoricer wallpaper set <image>`");
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, os::unix::fs::PermissionsExt, process};
+
+    fn write_stub(dir: &Path, name: &str, exit_code: i32) {
+        let stub: PathBuf = dir.join(name);
+
+        fs::write(
+            &stub,
+            format!(
+                "#!/bin/sh\necho \"$@\" > \"$(dirname \"$0\")/args\"\nexit {}\n",
+                exit_code
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn runs_the_package_helper_found_on_the_path() {
+        let dir: PathBuf = env::temp_dir().join(format!("autoricer-helper-{}", process::id()));
+        let packages: Vec<String> = vec![String::from("kitty"), String::from("waybar")];
+
+        fs::create_dir_all(&dir).unwrap();
+        write_stub(&dir, "paru", 0);
+
+        let (helper, helper_path) = detect_package_helper_in(dir.as_os_str()).unwrap();
+        assert_eq!(helper, "paru");
+
+        run_package_helper(helper, &helper_path, &packages, true).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("args")).unwrap(),
+            "-S --needed --noconfirm kitty waybar\n"
+        );
+
+        run_package_helper(helper, &helper_path, &packages, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("args")).unwrap(),
+            "-S --needed kitty waybar\n"
+        );
+
+        write_stub(&dir, "paru", 1);
+
+        let error: io::Error =
+            run_package_helper(helper, &helper_path, &packages, true).unwrap_err();
+        assert!(error.to_string().starts_with("paru exited with"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{hypr::includes::ConfigSet, DetectedKeyboard, KeyboardConfig, Paths};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+// Looks for the keyboard layout the system currently uses; the user's own Hyprland config wins over system files
+pub fn detect_keyboard_config(paths: &Paths) -> Option<DetectedKeyboard> {
+    detect_from_hypr_config(paths)
+        .or_else(|| detect_from_xorg_conf(&paths.etc.join("X11/xorg.conf.d/00-keyboard.conf")))
+        .or_else(|| detect_from_vconsole_conf(&paths.etc.join("vconsole.conf")))
+}
+
+// Has to run before the dotfiles overwrite ~/.config/hypr
+fn detect_from_hypr_config(paths: &Paths) -> Option<DetectedKeyboard> {
+    let configs: ConfigSet = ConfigSet::load(&paths.hypr_config, &paths.home).ok()?;
+    let layout: String = configs.get("input:kb_layout")?;
+
+    Some(DetectedKeyboard {
+        keyboard: KeyboardConfig::from_settings(
+            &layout,
+            &configs.get("input:kb_variant").unwrap_or_default(),
+            &configs.get("input:kb_model").unwrap_or_default(),
+            &configs.get("input:kb_options").unwrap_or_default(),
+        ),
+        source: paths.hypr_config.clone(),
+    })
+}
+
+// Written by `localectl set-x11-keymap`, e.g. `Option "XkbLayout" "de"`
+fn detect_from_xorg_conf(path: &PathBuf) -> Option<DetectedKeyboard> {
+    static XKB_OPTION_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"(?m)^\s*Option\s+"(Xkb\w+)"\s+"([^"]*)""#).unwrap());
+
+    let contents: String = fs::read_to_string(path).ok()?;
+    let settings: BTreeMap<&str, &str> = XKB_OPTION_RE
+        .captures_iter(&contents)
+        .map(|captures| {
+            let (_, [key, value]) = captures.extract();
+            (key, value)
+        })
+        .collect();
+
+    Some(DetectedKeyboard {
+        keyboard: KeyboardConfig::from_settings(
+            settings
+                .get("XkbLayout")
+                .filter(|layout| !layout.is_empty())?,
+            settings.get("XkbVariant").unwrap_or(&""),
+            settings.get("XkbModel").unwrap_or(&""),
+            settings.get("XkbOptions").unwrap_or(&""),
+        ),
+        source: path.clone(),
+    })
+}
+
+// Newer systemd versions store XKBLAYOUT next to the console KEYMAP, otherwise the KEYMAP is translated
+fn detect_from_vconsole_conf(path: &PathBuf) -> Option<DetectedKeyboard> {
+    let contents: String = fs::read_to_string(path).ok()?;
+    let settings: BTreeMap<&str, &str> = contents
+        .lines()
+        .filter_map(|line| line.trim().split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim().trim_matches('"')))
+        .collect();
+
+    let keyboard: KeyboardConfig = match settings
+        .get("XKBLAYOUT")
+        .filter(|layout| !layout.is_empty())
+    {
+        Some(layout) => KeyboardConfig::from_settings(
+            layout,
+            settings.get("XKBVARIANT").unwrap_or(&""),
+            settings.get("XKBMODEL").unwrap_or(&""),
+            settings.get("XKBOPTIONS").unwrap_or(&""),
+        ),
+        None => {
+            let keymap: &str = settings.get("KEYMAP")?;
+
+            KeyboardConfig::from_settings(keymap_to_layout(keymap), keymap_variant(keymap), "", "")
+        }
+    };
+
+    Some(DetectedKeyboard {
+        keyboard,
+        source: path.clone(),
+    })
+}
+
+// Console keymaps are mostly named after the XKB layout, e.g. `de-latin1`
+fn keymap_to_layout(keymap: &str) -> &str {
+    let name: &str = keymap.split('-').next().unwrap_or(keymap);
+
+    // Swiss keymaps are named after the language, e.g. `de_CH-latin1`
+    if matches!(name, "de_CH" | "fr_CH") {
+        return "ch";
+    }
+
+    let layout: &str = name.split('_').next().unwrap_or(name);
+
+    match layout {
+        "uk" => "gb",
+        "sg" | "sf" => "ch",
+        "cf" => "ca",
+        "dvorak" | "colemak" => "us",
+        _ => layout,
+    }
+}
+
+// `de-latin1-nodeadkeys` is the `nodeadkeys` variant of `de`
+fn keymap_variant(keymap: &str) -> &str {
+    match keymap.ends_with("-nodeadkeys") {
+        true => "nodeadkeys",
+        false => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+
+    // Everything points into the temp dir, so neither the real ~/.config nor /etc is read
+    fn paths(name: &str) -> Paths {
+        let dir: PathBuf =
+            env::temp_dir().join(format!("autoricer-detect-{}-{}", name, process::id()));
+
+        fs::create_dir_all(dir.join("etc/X11/xorg.conf.d")).unwrap();
+
+        Paths {
+            home: dir.clone(),
+            config: dir.join(".config"),
+            documents: dir.join("Documents"),
+            repo: dir.join("dotfiles"),
+            hypr_config: dir.join(".config/hypr/hyprland.conf"),
+            wallpapers: dir.join("Documents/wallpapers"),
+            pacman_db: dir.join("pacman"),
+            xkb_rules: dir.join("evdev.xml"),
+            etc: dir.join("etc"),
+            sys: dir.join("sys"),
+            proc: dir.join("proc"),
+        }
+    }
+
+    #[test]
+    fn reads_the_xkb_settings_of_vconsole_conf() {
+        let paths: Paths = paths("xkblayout");
+        let vconsole: PathBuf = paths.etc.join("vconsole.conf");
+
+        fs::write(
+            &vconsole,
+            "KEYMAP=de-latin1\nXKBLAYOUT=\"de,us\"\nXKBVARIANT=nodeadkeys\nXKBMODEL=pc105\nXKBOPTIONS=grp:alt_shift_toggle\n",
+        )
+        .unwrap();
+
+        let detected: DetectedKeyboard = detect_keyboard_config(&paths).unwrap();
+
+        assert_eq!(detected.keyboard.layouts, ["de", "us"]);
+        assert_eq!(detected.keyboard.variants, ["nodeadkeys"]);
+        assert_eq!(detected.keyboard.model, "pc105");
+        assert_eq!(detected.keyboard.options, ["grp:alt_shift_toggle"]);
+        assert_eq!(detected.source, vconsole);
+
+        fs::remove_dir_all(&paths.home).unwrap();
+    }
+
+    #[test]
+    fn translates_the_console_keymap() {
+        let paths: Paths = paths("keymap");
+
+        fs::write(
+            paths.etc.join("vconsole.conf"),
+            "KEYMAP=de-latin1-nodeadkeys\nFONT=eurlatgr\n",
+        )
+        .unwrap();
+
+        let keyboard: KeyboardConfig = detect_keyboard_config(&paths).unwrap().keyboard;
+
+        assert_eq!(keyboard.layouts, ["de"]);
+        assert_eq!(keyboard.variants, ["nodeadkeys"]);
+
+        fs::write(paths.etc.join("vconsole.conf"), "KEYMAP=uk\n").unwrap();
+
+        let keyboard: KeyboardConfig = detect_keyboard_config(&paths).unwrap().keyboard;
+
+        assert_eq!(keyboard.layouts, ["gb"]);
+        assert!(keyboard.variants.is_empty());
+
+        fs::remove_dir_all(&paths.home).unwrap();
+    }
+
+    #[test]
+    fn prefers_xorg_conf_over_vconsole_conf() {
+        let paths: Paths = paths("xorg");
+        let xorg: PathBuf = paths.etc.join("X11/xorg.conf.d/00-keyboard.conf");
+
+        fs::write(paths.etc.join("vconsole.conf"), "KEYMAP=us\n").unwrap();
+        fs::write(
+            &xorg,
+            "\
+# Written by systemd-localed(8), read by systemd-localed and Xorg. It's
+# probably wise not to edit this file manually. Use localectl(1) to
+# update this file.
+Section \"InputClass\"
+        Identifier \"system-keyboard\"
+        MatchIsKeyboard \"on\"
+        Option \"XkbLayout\" \"ch,fr\"
+        Option \"XkbVariant\" \",\"
+        Option \"XkbOptions\" \"caps:escape\"
+EndSection
+",
+        )
+        .unwrap();
+
+        let detected: DetectedKeyboard = detect_keyboard_config(&paths).unwrap();
+
+        assert_eq!(detected.keyboard.layouts, ["ch", "fr"]);
+        assert!(detected.keyboard.variants.is_empty());
+        assert!(detected.keyboard.model.is_empty());
+        assert_eq!(detected.keyboard.options, ["caps:escape"]);
+        assert_eq!(detected.source, xorg);
+
+        // An empty layout falls through to vconsole.conf
+        fs::write(&xorg, "Option \"XkbLayout\" \"\"\n").unwrap();
+
+        let keyboard: KeyboardConfig = detect_keyboard_config(&paths).unwrap().keyboard;
+
+        assert_eq!(keyboard.layouts, ["us"]);
+
+        fs::remove_dir_all(&paths.home).unwrap();
+    }
+
+    #[test]
+    fn maps_console_keymaps_to_layouts() {
+        for (keymap, layout) in [
+            ("de-latin1", "de"),
+            ("de-latin1-nodeadkeys", "de"),
+            ("uk", "gb"),
+            ("de_CH-latin1", "ch"),
+            ("fr_CH", "ch"),
+            ("sg", "ch"),
+            ("cf", "ca"),
+            ("dvorak-programmer", "us"),
+            ("us", "us"),
+        ] {
+            assert_eq!(keymap_to_layout(keymap), layout, "{}", keymap);
+        }
+    }
+
+    #[test]
+    fn finds_nothing_without_config_files() {
+        let paths: Paths = paths("nothing");
+
+        assert!(detect_keyboard_config(&paths).is_none());
+
+        fs::remove_dir_all(&paths.home).unwrap();
+    }
+}
@@ -0,0 +1,390 @@
+use crate::{
+    prompt,
+    utils::{
+        contents::get_kb_switch_options,
+        helper_functions::{read_input, read_raw_input},
+    },
+    warning, KBLayout, KeyboardConfig, XkbRules,
+};
+use colored::Colorize;
+use search::{search_layouts, LayoutMatch};
+use std::io;
+
+pub mod detect;
+pub mod search;
+pub mod xkb;
+
+// XKB supports at most four layout groups
+const MAX_LAYOUTS: usize = 4;
+
+// A layout code with the variant picked through the search, if any
+type LayoutChoice = (String, Option<String>);
+
+// Asks for layouts, variants, model and options until they form a valid combination
+pub fn get_keyboard_config(rules: &XkbRules) -> io::Result<KBLayout> {
+    loop {
+        let (layouts, picked_variants): (Vec<String>, Vec<Option<String>>) =
+            match get_kb_layout_codes(rules)? {
+                Some(layouts) => layouts.into_iter().unzip(),
+                None => return Ok(KBLayout::Default),
+            };
+
+        let mut variants: Vec<String> = Vec::new();
+
+        // Variants picked through the search don't have to be asked for again
+        for (layout, variant) in layouts.iter().zip(picked_variants) {
+            match variant {
+                Some(variant) => variants.push(variant),
+                None => variants.push(get_kb_variant(rules, layout)?),
+            }
+        }
+
+        prompt!("Keyboard model (e.g. pc105). Press Enter for the default model:");
+
+        // XKB names are case sensitive, e.g. the OADG109A model or phonetic_YAZHERTY
+        let model: String = read_raw_input()?;
+
+        let mut options: Vec<String> = Vec::new();
+
+        if layouts.len() > 1 {
+            options.push(get_kb_switch_option()?);
+        }
+
+        prompt!(
+            "Additional XKB options separated by commas (e.g. caps:escape). Press Enter for none:"
+        );
+
+        options.extend(split_list(&read_raw_input()?));
+
+        // Trailing empty variants carry no information
+        while variants.last().is_some_and(|variant| variant.is_empty()) {
+            variants.pop();
+        }
+
+        let keyboard: KeyboardConfig = KeyboardConfig {
+            layouts,
+            variants,
+            model,
+            options,
+        };
+
+        match validate_keyboard_config(&keyboard, rules) {
+            Ok(()) => return Ok(KBLayout::Change(keyboard)),
+            Err(message) => warning!(format!("==> Invalid keyboard configuration: {}", message)),
+        }
+    }
+}
+
+// Every comma separated entry is either a layout code or a search term; returns `None` if the user quits
+fn get_kb_layout_codes(rules: &XkbRules) -> io::Result<Option<Vec<LayoutChoice>>> {
+    'input: loop {
+        prompt!("Please enter one or more keyboard layouts separated by commas (e.g. de,us) or search for one (e.g. german swiss). Press l to see a [l]ist of available options or q to [q]uit:");
+
+        let input: String = read_input()?;
+
+        match input.as_str() {
+            "q" | "quit" => return Ok(None),
+            "l" | "list" => rules
+                .layouts
+                .iter()
+                .for_each(|layout| println!("{} -> {}", layout.name, layout.description)),
+            _ => {
+                let mut layouts: Vec<LayoutChoice> = Vec::new();
+
+                for entry in input
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|entry| !entry.is_empty())
+                {
+                    if rules.layout(entry).is_some() {
+                        layouts.push((entry.to_string(), None));
+                        continue;
+                    }
+
+                    match pick_layout(rules, entry)? {
+                        Some(found) => layouts.push((found.layout, found.variant)),
+                        None => continue 'input,
+                    }
+                }
+
+                if !layouts.is_empty() {
+                    return Ok(Some(layouts));
+                }
+            }
+        }
+    }
+}
+
+// Shows the best matches for a search term and lets the user pick one by number
+fn pick_layout(rules: &XkbRules, query: &str) -> io::Result<Option<LayoutMatch>> {
+    let mut matches: Vec<LayoutMatch> = search_layouts(rules, query);
+
+    if matches.is_empty() {
+        warning!(format!("==> No keyboard layout matches: {}", query));
+        return Ok(None);
+    }
+
+    println!();
+
+    for (number, found) in matches.iter().enumerate() {
+        let code: String = match &found.variant {
+            Some(variant) => format!("{} {}", found.layout, variant),
+            None => found.layout.clone(),
+        };
+
+        println!("{} -> {} ({})", number + 1, code.bold(), found.description);
+    }
+
+    loop {
+        prompt!(format!(
+            "Enter the number of your layout for \"{}\" or press Enter to search again:",
+            query
+        ));
+
+        let input: String = read_input()?;
+
+        if input.is_empty() {
+            return Ok(None);
+        }
+
+        match input.parse::<usize>() {
+            Ok(number) if (1..=matches.len()).contains(&number) => {
+                return Ok(Some(matches.swap_remove(number - 1)))
+            }
+            _ => prompt!(format!(
+                "==> Please enter a number between 1 and {}!",
+                matches.len()
+            )),
+        }
+    }
+}
+
+fn get_kb_variant(rules: &XkbRules, layout: &str) -> io::Result<String> {
+    loop {
+        prompt!(format!(
+            "Variant for [{}] (e.g. nodeadkeys, intl). Press l to see a [l]ist of variants or Enter for the default variant:",
+            layout
+        ));
+
+        let input: String = read_raw_input()?;
+
+        match input.to_lowercase().as_str() {
+            "l" | "list" => match rules.layout(layout) {
+                Some(layout) if !layout.variants.is_empty() => layout
+                    .variants
+                    .iter()
+                    .for_each(|variant| println!("{} -> {}", variant.name, variant.description)),
+                _ => warning!("==> No variants known for this layout"),
+            },
+            _ if input.is_empty() || rules.has_variant(layout, &input) => return Ok(input),
+            _ => warning!(format!("==> Unknown variant for [{}]: {}", layout, input)),
+        }
+    }
+}
+
+fn get_kb_switch_option() -> io::Result<String> {
+    let switch_options: Vec<(&str, &str)> = get_kb_switch_options();
+
+    for (number, (_, keys)) in switch_options.iter().enumerate() {
+        println!("{} -> {}", number + 1, keys);
+    }
+
+    loop {
+        prompt!("Which keys should switch between the layouts? [1]");
+
+        let input: String = read_input()?;
+
+        if input.is_empty() {
+            return Ok(switch_options[0].0.to_string());
+        }
+
+        match input.parse::<usize>() {
+            Ok(number) if (1..=switch_options.len()).contains(&number) => {
+                return Ok(switch_options[number - 1].0.to_string())
+            }
+            _ => prompt!(format!(
+                "==> Please enter a number between 1 and {}!",
+                switch_options.len()
+            )),
+        }
+    }
+}
+
+// Checks that layouts, variants, model and options make sense together
+pub fn validate_keyboard_config(keyboard: &KeyboardConfig, rules: &XkbRules) -> Result<(), String> {
+    if keyboard.layouts.is_empty() {
+        return Err("at least one layout is required".to_string());
+    }
+
+    if keyboard.layouts.len() > MAX_LAYOUTS {
+        return Err(format!("at most {} layouts are supported", MAX_LAYOUTS));
+    }
+
+    if let Some(layout) = keyboard
+        .layouts
+        .iter()
+        .find(|layout| rules.layout(layout).is_none())
+    {
+        return Err(format!("unknown layout `{}`", layout));
+    }
+
+    if keyboard.variants.len() > keyboard.layouts.len() {
+        return Err("there are more variants than layouts".to_string());
+    }
+
+    if let Some((layout, variant)) =
+        keyboard
+            .layouts
+            .iter()
+            .zip(&keyboard.variants)
+            .find(|(layout, variant)| {
+                !variant.is_empty()
+                    && (!is_xkb_name(variant) || !rules.has_variant(layout, variant))
+            })
+    {
+        return Err(format!(
+            "unknown variant `{}` for layout `{}`",
+            variant, layout
+        ));
+    }
+
+    if !keyboard.model.is_empty()
+        && (!is_xkb_name(&keyboard.model) || !rules.has_model(&keyboard.model))
+    {
+        return Err(format!("unknown model `{}`", keyboard.model));
+    }
+
+    if let Some(option) = keyboard.options.iter().find(|option| {
+        !option.split_once(':').is_some_and(|(group, name)| {
+            is_xkb_name(group) && is_xkb_name(name) && !group.is_empty() && !name.is_empty()
+        }) || !rules.has_option(option)
+    }) {
+        return Err(format!(
+            "unknown option `{}`, expected <group>:<name>",
+            option
+        ));
+    }
+
+    let switch_options: usize = keyboard
+        .options
+        .iter()
+        .filter(|option| option.starts_with("grp:"))
+        .count();
+
+    // Without a switch key the layouts can still be changed with `switchxkblayout` binds
+    match (keyboard.layouts.len(), switch_options) {
+        (_, 0) | (2.., 1) => Ok(()),
+        (1, _) => Err("a layout switch key needs at least two layouts".to_string()),
+        _ => Err("only one switch key option (grp:...) can be set".to_string()),
+    }
+}
+
+fn is_xkb_name(name: &str) -> bool {
+    name.chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '(' | ')'))
+}
+
+fn split_list(input: &str) -> Vec<String> {
+    input
+        .split([',', ' '])
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keyboard::xkb::parse_xkb_rules;
+
+    fn keyboard(layouts: &str, variants: &str, options: &str) -> KeyboardConfig {
+        KeyboardConfig {
+            layouts: split_list(layouts),
+            variants: variants.split(',').map(String::from).collect(),
+            model: String::new(),
+            options: split_list(options),
+        }
+    }
+
+    fn rules() -> XkbRules {
+        parse_xkb_rules(include_str!("../../tests/fixtures/evdev.xml"))
+    }
+
+    #[test]
+    fn accepts_layouts_with_variants_and_options() {
+        let rules: XkbRules = rules();
+
+        assert_eq!(
+            validate_keyboard_config(
+                &keyboard("de,us", "nodeadkeys,", "grp:alt_shift_toggle,caps:escape"),
+                &rules
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate_keyboard_config(&keyboard("ch", "", "caps:escape"), &rules),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_more_variants_than_layouts() {
+        assert_eq!(
+            validate_keyboard_config(&keyboard("de", "nodeadkeys,intl", ""), &rules()),
+            Err("there are more variants than layouts".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_more_than_four_layouts() {
+        assert_eq!(
+            validate_keyboard_config(&keyboard("de,us,ch,fr,ru", "", ""), &rules()),
+            Err("at most 4 layouts are supported".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_a_switch_key_with_one_layout() {
+        assert_eq!(
+            validate_keyboard_config(&keyboard("de", "", "grp:alt_shift_toggle"), &rules()),
+            Err("a layout switch key needs at least two layouts".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_two_switch_keys() {
+        let mut rules: XkbRules = rules();
+
+        // The fixture only knows one `grp:` option, without options every option is accepted
+        rules.options.clear();
+
+        assert_eq!(
+            validate_keyboard_config(
+                &keyboard("de,us", "", "grp:alt_shift_toggle,grp:win_space_toggle"),
+                &rules
+            ),
+            Err("only one switch key option (grp:...) can be set".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_bad_option_syntax() {
+        let mut rules: XkbRules = rules();
+
+        assert!(validate_keyboard_config(&keyboard("de", "", "caps:nocaps"), &rules).is_err());
+
+        // Only the syntax is checked without known options
+        rules.options.clear();
+
+        for option in ["caps", "caps:", ":escape", "caps:esc;ape"] {
+            assert_eq!(
+                validate_keyboard_config(&keyboard("de", "", option), &rules),
+                Err(format!(
+                    "unknown option `{}`, expected <group>:<name>",
+                    option
+                ))
+            );
+        }
+    }
+}
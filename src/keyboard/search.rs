@@ -0,0 +1,154 @@
+use crate::XkbRules;
+
+// Number of matches shown to the user
+const MAX_MATCHES: usize = 10;
+
+pub struct LayoutMatch {
+    pub layout: String,
+    pub variant: Option<String>,
+    pub description: String,
+    score: usize,
+}
+
+// Matches every word of the query against layout codes, names and variant descriptions
+pub fn search_layouts(rules: &XkbRules, query: &str) -> Vec<LayoutMatch> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let mut matches: Vec<LayoutMatch> = Vec::new();
+
+    if words.is_empty() {
+        return matches;
+    }
+
+    for layout in &rules.layouts {
+        let text: String = format!("{} {}", layout.name, layout.description);
+
+        if let Some(score) = score_text(&words, &text) {
+            matches.push(LayoutMatch {
+                layout: layout.name.clone(),
+                variant: None,
+                description: layout.description.clone(),
+                // Prefer the plain layout over its variants when both match equally well
+                score: score + 1,
+            });
+        }
+
+        for variant in &layout.variants {
+            let text: String = format!("{} {} {}", layout.name, variant.name, variant.description);
+
+            if let Some(score) = score_text(&words, &text) {
+                matches.push(LayoutMatch {
+                    layout: layout.name.clone(),
+                    variant: Some(variant.name.clone()),
+                    description: variant.description.clone(),
+                    score,
+                });
+            }
+        }
+    }
+
+    matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.layout.cmp(&b.layout)));
+    matches.truncate(MAX_MATCHES);
+
+    matches
+}
+
+// All words have to match somewhere, otherwise the candidate is dropped
+fn score_text(words: &[String], text: &str) -> Option<usize> {
+    let text: String = text.to_lowercase();
+    let text_words: Vec<&str> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    words
+        .iter()
+        .map(|word| score_word(word, &text, &text_words))
+        .sum()
+}
+
+fn score_word(word: &str, text: &str, text_words: &[&str]) -> Option<usize> {
+    if text_words.contains(&word) {
+        return Some(100);
+    }
+
+    if text_words
+        .iter()
+        .any(|text_word| text_word.starts_with(word))
+    {
+        return Some(80);
+    }
+
+    if text.contains(word) {
+        return Some(60);
+    }
+
+    // Catches spellings like "swiss" for "switzerland"
+    let prefix: usize = text_words
+        .iter()
+        .map(|text_word| common_prefix(word, text_word))
+        .max()
+        .unwrap_or(0);
+
+    if prefix >= 3 {
+        return Some(40 * prefix / word.chars().count());
+    }
+
+    is_subsequence(word, text).then_some(10)
+}
+
+fn common_prefix(a: &str, b: &str) -> usize {
+    a.chars().zip(b.chars()).take_while(|(a, b)| a == b).count()
+}
+
+fn is_subsequence(word: &str, text: &str) -> bool {
+    let mut chars = text.chars();
+
+    word.chars().all(|c| chars.any(|t| t == c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keyboard::xkb::parse_xkb_rules;
+
+    fn search(query: &str) -> Vec<(String, Option<String>)> {
+        let rules: XkbRules = parse_xkb_rules(include_str!("../../tests/fixtures/evdev.xml"));
+
+        search_layouts(&rules, query)
+            .into_iter()
+            .map(|found| (found.layout, found.variant))
+            .collect()
+    }
+
+    #[test]
+    fn plain_layouts_rank_before_their_variants() {
+        assert_eq!(
+            search("german swiss"),
+            [
+                (String::from("ch"), None),
+                (String::from("ch"), Some(String::from("de_nodeadkeys"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn variants_are_found_by_their_description() {
+        assert_eq!(
+            search("dvorak"),
+            [(String::from("us"), Some(String::from("dvorak")))]
+        );
+        assert_eq!(
+            search("russian phonetic")[0],
+            (String::from("ru"), Some(String::from("phonetic_YAZHERTY")))
+        );
+    }
+
+    #[test]
+    fn layouts_matching_equally_well_are_ordered_by_code() {
+        assert_eq!(
+            search("german")[..2],
+            [(String::from("ch"), None), (String::from("de"), None)]
+        );
+        assert!(search("klingon").is_empty());
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{utils::contents::get_kb_layouts, warning, XkbItem, XkbLayout, XkbRules};
+use colored::Colorize;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::{fs, path::Path};
+
+// Loads layouts, variants, models and options from the XKB rules, falling back to the built-in layout list
+pub fn load_xkb_rules(xkb_rules: &Path) -> XkbRules {
+    match fs::read_to_string(xkb_rules).map(|xml| parse_xkb_rules(&xml)) {
+        Ok(rules) if !rules.layouts.is_empty() => rules,
+        _ => {
+            warning!(format!(
+                "==> Could not read {}. Using the built-in list of keyboard layouts",
+                xkb_rules.display()
+            ));
+
+            fallback_xkb_rules()
+        }
+    }
+}
+
+fn fallback_xkb_rules() -> XkbRules {
+    XkbRules {
+        models: Vec::new(),
+        layouts: get_kb_layouts()
+            .into_iter()
+            .map(|(name, description)| XkbLayout {
+                name: name.to_string(),
+                description: description.to_string(),
+                variants: Vec::new(),
+            })
+            .collect(),
+        options: Vec::new(),
+    }
+}
+
+// Parses evdev.xml; only `<name>` and `<description>` of models, layouts, variants and options are needed
+pub fn parse_xkb_rules(xml: &str) -> XkbRules {
+    static TOKEN_RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?s)<!--.*?-->|<\?.*?\?>|<(/?)([\w:]+)[^>]*?(/?)>|([^<]+)").unwrap()
+    });
+
+    let mut rules: XkbRules = XkbRules {
+        models: Vec::new(),
+        layouts: Vec::new(),
+        options: Vec::new(),
+    };
+
+    let mut stack: Vec<String> = Vec::new();
+    let mut text: String = String::new();
+    let mut item: XkbItem = empty_item();
+
+    for token in TOKEN_RE.captures_iter(xml) {
+        if let Some(content) = token.get(4) {
+            text.push_str(content.as_str());
+            continue;
+        }
+
+        let Some(tag) = token.get(2).map(|tag| tag.as_str()) else {
+            continue;
+        };
+
+        // Self-closing tags like `<vendor/>` carry nothing of interest
+        if token.get(3).is_some_and(|slash| !slash.is_empty()) {
+            continue;
+        }
+
+        if token.get(1).is_some_and(|slash| slash.is_empty()) {
+            stack.push(tag.to_string());
+            text.clear();
+            continue;
+        }
+
+        stack.pop();
+
+        // Name of the element the current `<configItem>` describes
+        let owner: Option<&str> = stack
+            .iter()
+            .rev()
+            .find(|element| *element != "configItem")
+            .map(String::as_str);
+
+        match tag {
+            "name" if stack.last().is_some_and(|e| e == "configItem") => {
+                item.name = unescape_xml(text.trim())
+            }
+            "description" if stack.last().is_some_and(|e| e == "configItem") => {
+                item.description = unescape_xml(text.trim())
+            }
+            "configItem" => {
+                let done: XkbItem = std::mem::replace(&mut item, empty_item());
+
+                match owner {
+                    Some("model") => rules.models.push(done),
+                    Some("layout") => rules.layouts.push(XkbLayout {
+                        name: done.name,
+                        description: done.description,
+                        variants: Vec::new(),
+                    }),
+                    Some("variant") => {
+                        if let Some(layout) = rules.layouts.last_mut() {
+                            layout.variants.push(done);
+                        }
+                    }
+                    Some("option") => rules.options.push(done),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+
+        text.clear();
+    }
+
+    rules.layouts.sort_by(|a, b| a.name.cmp(&b.name));
+
+    rules
+}
+
+fn empty_item() -> XkbItem {
+    XkbItem {
+        name: String::new(),
+        description: String::new(),
+    }
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVDEV: &str = include_str!("../../tests/fixtures/evdev.xml");
+
+    #[test]
+    fn parses_layouts_variants_models_and_options() {
+        let rules: XkbRules = parse_xkb_rules(EVDEV);
+
+        assert_eq!(
+            rules
+                .layouts
+                .iter()
+                .map(|layout| layout.name.as_str())
+                .collect::<Vec<&str>>(),
+            ["ch", "de", "gb", "ru", "us"]
+        );
+        assert_eq!(rules.layout("de").unwrap().description, "German");
+        assert_eq!(
+            rules.layout("gb").unwrap().variants[0].description,
+            "English (UK, Macintosh & Apple keyboards)"
+        );
+
+        assert!(rules.has_variant("us", "intl"));
+        assert!(rules.has_variant("ru", "phonetic_YAZHERTY"));
+        assert!(!rules.has_variant("ru", "phonetic_yazherty"));
+        assert!(!rules.has_variant("de", "intl"));
+
+        assert!(rules.has_model("pc105"));
+        assert!(rules.has_model("OADG109A"));
+        assert!(!rules.has_model("pc104"));
+
+        // Group headers like `grp` aren't options themselves
+        assert_eq!(rules.options.len(), 2);
+        assert!(rules.has_option("grp:alt_shift_toggle"));
+        assert!(rules.has_option("caps:escape"));
+        assert!(!rules.has_option("grp"));
+    }
+}
@@ -1,13 +1,47 @@
-use crate::utils::{contents::print_installer_info, helper_functions::pause, types::*};
+use crate::utils::{
+    answers::load_answers, contents::print_installer_info, helper_functions::pause, types::*,
+};
 use colored::Colorize;
+use commands::run_command;
 use installer::*;
-use std::io;
+use keyboard::{detect::detect_keyboard_config, xkb::load_xkb_rules};
+use std::{env, io, path::Path};
 
+mod commands;
+mod hardware;
+mod hypr;
 mod installer;
+mod keyboard;
 mod utils;
+mod waybar;
 
 fn main() -> io::Result<()> {
     let paths: Paths = Paths::build(); // All paths needed
+    let args: Vec<String> = env::args().skip(1).collect();
+    let noconfirm: bool = args.iter().any(|arg| arg == "--noconfirm"); // Passed through to the package helper
+    let answers_index: Option<usize> = args.iter().position(|arg| arg == "--answers");
+
+    // Subcommands; the file following `--answers` isn't one
+    if let Some(index) = args.iter().enumerate().position(|(index, arg)| {
+        !arg.starts_with('-') && Some(index) != answers_index.map(|i| i + 1)
+    }) {
+        return run_command(&args[index], &args[index + 1..], &paths);
+    }
+
+    let answers: Answers = match answers_index.map(|index| args.get(index + 1)) {
+        Some(Some(path)) => match load_answers(Path::new(path)) {
+            Ok(answers) => answers,
+            Err(error) => {
+                error!(format!("Could not read the answers file {}", path), error);
+                return Err(error);
+            }
+        },
+        Some(None) => {
+            error!("Usage: autoricer --answers <file>");
+            return Err(io::Error::from(io::ErrorKind::InvalidInput));
+        }
+        None => Answers::default(),
+    };
 
     // Installer
     print_installer_info();
@@ -37,12 +71,12 @@ fn main() -> io::Result<()> {
         }
     }
 
-    match cleanup_repo(&paths) {
-        Ok(()) => success!("==> Cleanup was successful"),
-        Err(error) => error!(
-            "There was an error while cleaning up ~/Downloads/arch-everforest",
-            error
-        ),
+    let missing_packages: Vec<String> = check_dependencies(&paths.pacman_db)?;
+
+    match install_missing_packages(&missing_packages, noconfirm) {
+        Ok(PackageInstall::Installed) => success!("==> Successfully installed missing packages"),
+        Ok(PackageInstall::Skipped) => {}
+        Err(error) => error!("Installing missing packages failed", error),
     }
 
     match create_backup(&paths.config, &paths.documents) {
@@ -62,7 +96,29 @@ fn main() -> io::Result<()> {
         }
     }
 
-    match copy_config_dirs_recursively(&paths.repo, &paths.config) {
+    // Has to happen before ~/.config/hypr gets overwritten
+    let detected_keyboard: Option<DetectedKeyboard> = detect_keyboard_config(&paths);
+
+    let skipped_dirs: Vec<String> = select_config_dirs(&paths)?;
+
+    // Runs after the selection so the zsh directory is still there to be detected
+    match cleanup_repo(&paths, &skipped_dirs) {
+        Ok(()) => success!("==> Cleanup was successful"),
+        Err(error) => error!(
+            "There was an error while cleaning up ~/Downloads/arch-everforest",
+            error
+        ),
+    }
+
+    let kept_binds: Vec<Bind> = match check_bind_conflicts(&paths, &skipped_dirs) {
+        Ok(binds) => binds,
+        Err(error) => {
+            error!("Comparing keybindings failed", error);
+            Vec::new()
+        }
+    };
+
+    match copy_config_dirs_recursively(&paths.repo, &paths.config, &skipped_dirs) {
         Ok(()) => {
             success!("==> Successfully copied config files to ~/.config!");
         }
@@ -72,19 +128,93 @@ fn main() -> io::Result<()> {
         }
     }
 
-    match change_settings(&paths.hypr_config) {
-        Ok(HyprConfig::Modified) => success!("==> Successfully modified Hypr config!"),
-        Ok(HyprConfig::Default) => success!("==> Using default Hypr config"),
-        Err(error) => error!("Modifying Hypr config failed", error),
+    if !kept_binds.is_empty() {
+        match carry_over_binds(&paths, &kept_binds) {
+            Ok(()) => success!("==> Kept your keybindings in ~/.config/hypr/user-binds.conf"),
+            Err(error) => error!("Could not keep your keybindings", error),
+        }
     }
 
-    match install_cli_utilities(&paths.home, &paths.config) {
+    // The user's own Hypr config is left alone if the hypr directory was skipped
+    let edit_hypr: bool = !skipped_dirs.iter().any(|dir| dir == "hypr");
+
+    let rules: XkbRules = load_xkb_rules(&paths.xkb_rules);
+
+    if edit_hypr {
+        match migrate_hypr_config(&paths) {
+            Ok(HyprConfig::Modified) => success!("==> Successfully migrated Hypr config!"),
+            Ok(HyprConfig::Default) => success!("==> Kept Hypr config unchanged"),
+            Err(error) => error!("Migrating Hypr config failed", error),
+        }
+
+        match change_settings(&paths, &rules, detected_keyboard) {
+            Ok(HyprConfig::Modified) => success!("==> Successfully modified Hypr config!"),
+            Ok(HyprConfig::Default) => success!("==> Using default Hypr config"),
+            Err(error) => error!("Modifying Hypr config failed", error),
+        }
+
+        match change_default_apps(&paths, &answers) {
+            Ok(HyprConfig::Modified) => success!("==> Successfully changed default applications!"),
+            Ok(HyprConfig::Default) => success!("==> Using default applications"),
+            Err(error) => error!("Changing default applications failed", error),
+        }
+
+        match configure_input(&paths) {
+            Ok(HyprConfig::Modified) => success!("==> Successfully configured input devices!"),
+            Ok(HyprConfig::Default) => success!("==> Using default input settings"),
+            Err(error) => error!("Configuring input devices failed", error),
+        }
+
+        match configure_monitors(&paths) {
+            Ok(HyprConfig::Modified) => success!("==> Successfully configured monitors!"),
+            Ok(HyprConfig::Default) => success!("==> Using default monitor config"),
+            Err(error) => error!("Configuring monitors failed", error),
+        }
+    }
+
+    // Waybar is adapted even if the Hypr config is left alone
+    match configure_laptop(&paths, edit_hypr) {
+        Ok(HyprConfig::Modified) => {
+            success!("==> Successfully adapted the config to your machine!")
+        }
+        Ok(HyprConfig::Default) => success!("==> No laptop specific changes needed"),
+        Err(error) => error!("Adapting the config to your machine failed", error),
+    }
+
+    if edit_hypr {
+        if let Err(error) = validate_hypr_config(&paths, &rules) {
+            error!("The edited Hypr config is invalid. Exiting...", error);
+            return Err(error);
+        }
+    }
+
+    match install_cli_utilities(&paths.home, &paths.config, &skipped_dirs) {
         Ok(DownloadStatus::Success) => success!("==> Successfully installed CLI utilities"),
         Ok(_) => {}
         Err(error) => error!("Installing CLI utilities failed", error),
     }
 
-    match after_install(&paths.repo) {
+    let reload_status: ReloadStatus = match reload_hyprland(&paths) {
+        Ok(ReloadStatus::ConfigErrors(errors)) => {
+            for message in &errors {
+                warning!(format!("==> Hyprland: {}", message));
+            }
+
+            let error: io::Error = io::Error::other(format!(
+                "Hyprland reported {} config error(s)",
+                errors.len()
+            ));
+            error!("Hyprland rejected the new config. Exiting...", error);
+            return Err(error);
+        }
+        Ok(status) => status,
+        Err(error) => {
+            error!("Could not reload Hyprland", error);
+            ReloadStatus::NotRunning
+        }
+    };
+
+    match after_install(&paths, reload_status) {
         Ok(()) => pause().unwrap(),
         Err(error) => error!("", error),
     }
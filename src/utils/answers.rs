@@ -0,0 +1,50 @@
+use crate::Answers;
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+pub fn load_answers(path: &Path) -> io::Result<Answers> {
+    parse_answers(&fs::read_to_string(path)?)
+        .map_err(|error| io::Error::new(error.kind(), format!("{}: {}", path.display(), error)))
+}
+
+// One `key=value` per line, e.g. `terminal=alacritty`; empty lines and `#` comments are skipped
+pub fn parse_answers(contents: &str) -> io::Result<Answers> {
+    let mut values: BTreeMap<String, String> = BTreeMap::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line: &str = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: expected `key=value`", index + 1),
+            ));
+        };
+
+        values.insert(key.trim().to_string(), value.trim().to_string());
+    }
+
+    Ok(Answers { values })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_key_value_lines() {
+        let answers: Answers = parse_answers(
+            "# Default applications\nmainMod = ALT\n\nmenu=rofi -show drun\nbrowser=\n",
+        )
+        .unwrap();
+
+        assert_eq!(answers.get("mainMod"), Some("ALT"));
+        assert_eq!(answers.get("menu"), Some("rofi -show drun"));
+        assert_eq!(answers.get("browser"), Some(""));
+        assert_eq!(answers.get("terminal"), None);
+        assert!(parse_answers("terminal kitty\n").is_err());
+    }
+}
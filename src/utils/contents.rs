@@ -1,3 +1,7 @@
+use crate::{
+    utils::pacman::parse_optdepends, AppVariable, EnvGroup, HyprOptionType, Migration,
+    MigrationAction, Program, Version,
+};
 use colored::Colorize;
 use std::collections::BTreeMap;
 
@@ -136,3 +140,403 @@ pub fn get_kb_layouts() -> BTreeMap<&'static str, &'static str> {
         ("sn", "Wolof"),
     ])
 }
+
+// XKB options for switching between multiple layouts
+pub fn get_kb_switch_options() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("grp:alt_shift_toggle", "Alt + Shift"),
+        ("grp:win_space_toggle", "Super + Space"),
+        ("grp:ctrl_shift_toggle", "Ctrl + Shift"),
+        ("grp:alt_space_toggle", "Alt + Space"),
+        ("grp:caps_toggle", "Caps Lock"),
+    ]
+}
+
+// Groups of environment variables which can be enabled and disabled as a whole
+pub fn get_env_groups() -> Vec<EnvGroup> {
+    vec![
+        EnvGroup {
+            name: "nvidia",
+            description: "Proprietary NVIDIA driver",
+            vars: vec![
+                ("LIBVA_DRIVER_NAME", "nvidia"),
+                ("NVD_BACKEND", "direct"),
+                ("GBM_BACKEND", "nvidia-drm"),
+                ("__GLX_VENDOR_LIBRARY_NAME", "nvidia"),
+            ],
+        },
+        EnvGroup {
+            name: "hidpi",
+            description: "Scale GTK and Qt applications on HiDPI screens",
+            vars: vec![("GDK_SCALE", "2"), ("QT_AUTO_SCREEN_SCALE_FACTOR", "1")],
+        },
+        EnvGroup {
+            name: "qt-wayland",
+            description: "Run Qt applications natively on Wayland",
+            vars: vec![
+                ("QT_QPA_PLATFORM", "wayland;xcb"),
+                ("QT_WAYLAND_DISABLE_WINDOWDECORATION", "1"),
+            ],
+        },
+        EnvGroup {
+            name: "cursor",
+            description: "Cursor size for XCursor and hyprcursor",
+            vars: vec![("XCURSOR_SIZE", "24"), ("HYPRCURSOR_SIZE", "24")],
+        },
+    ]
+}
+
+// Hyprland options the validator knows the value type of
+pub fn get_hypr_option_types() -> Vec<(&'static str, HyprOptionType)> {
+    use HyprOptionType::*;
+
+    vec![
+        ("general:gaps_in", Gaps),
+        ("general:gaps_out", Gaps),
+        ("general:border_size", Int),
+        ("general:resize_on_border", Bool),
+        ("general:allow_tearing", Bool),
+        ("general:layout", String),
+        ("decoration:rounding", Int),
+        ("decoration:active_opacity", Float),
+        ("decoration:inactive_opacity", Float),
+        ("decoration:blur:enabled", Bool),
+        ("decoration:blur:size", Int),
+        ("decoration:blur:passes", Int),
+        ("animations:enabled", Bool),
+        ("input:kb_layout", String),
+        ("input:kb_variant", String),
+        ("input:kb_model", String),
+        ("input:kb_options", String),
+        ("input:follow_mouse", Int),
+        ("input:sensitivity", Float),
+        ("input:accel_profile", String),
+        ("input:natural_scroll", Bool),
+        ("input:touchpad:natural_scroll", Bool),
+        ("input:touchpad:tap-to-click", Bool),
+        ("input:touchpad:disable_while_typing", Bool),
+        ("gestures:workspace_swipe", Bool),
+        ("dwindle:pseudotile", Bool),
+        ("dwindle:preserve_split", Bool),
+        ("misc:disable_hyprland_logo", Bool),
+        ("misc:force_default_wallpaper", Int),
+        ("xwayland:force_zero_scaling", Bool),
+    ]
+}
+
+// Variables of the Hypr config holding the default applications started by keybindings
+pub fn get_app_variables() -> Vec<AppVariable> {
+    vec![
+        AppVariable {
+            name: "terminal",
+            description: "Terminal",
+            candidates: vec!["kitty", "alacritty", "foot", "wezterm", "ghostty"],
+        },
+        AppVariable {
+            name: "menu",
+            description: "Application launcher",
+            candidates: vec!["wofi --show drun", "rofi -show drun", "fuzzel", "tofi-drun"],
+        },
+        AppVariable {
+            name: "fileManager",
+            description: "File manager",
+            candidates: vec!["kitty yazi", "thunar", "nautilus", "dolphin", "nemo"],
+        },
+        AppVariable {
+            name: "browser",
+            description: "Browser",
+            candidates: vec!["firefox", "chromium", "brave", "librewolf", "qutebrowser"],
+        },
+    ]
+}
+
+// hypridle listeners by a part of their `on-timeout` command, with the timeout in seconds on laptops and desktops
+pub fn get_idle_timeouts() -> Vec<(&'static str, u32, u32)> {
+    vec![
+        ("brightnessctl", 150, 300),
+        ("lock-session", 300, 600),
+        ("dpms off", 330, 900),
+        ("suspend", 1800, 3600),
+    ]
+}
+
+// Keys whose binds only work with a backlight
+pub fn get_brightness_keys() -> Vec<&'static str> {
+    vec!["XF86MonBrightnessUp", "XF86MonBrightnessDown"]
+}
+
+// Waybar modules only shown on laptops
+pub fn get_laptop_waybar_modules() -> Vec<&'static str> {
+    vec!["battery", "backlight"]
+}
+
+// Sections of the keybinding cheatsheet and the dispatchers listed in them
+pub fn get_bind_groups() -> Vec<(&'static str, Vec<&'static str>)> {
+    vec![
+        ("Applications", vec!["exec", "execr"]),
+        (
+            "Windows",
+            vec![
+                "killactive",
+                "forcekillactive",
+                "closewindow",
+                "togglefloating",
+                "setfloating",
+                "settiled",
+                "fullscreen",
+                "fullscreenstate",
+                "pseudo",
+                "pin",
+                "centerwindow",
+                "togglesplit",
+                "swapsplit",
+                "layoutmsg",
+                "togglegroup",
+                "changegroupactive",
+            ],
+        ),
+        (
+            "Focus and Movement",
+            vec![
+                "movefocus",
+                "movewindow",
+                "swapwindow",
+                "resizeactive",
+                "moveactive",
+                "resizewindow",
+                "cyclenext",
+                "focuswindow",
+                "focusmonitor",
+                "focusurgentorlast",
+                "focuscurrentorlast",
+            ],
+        ),
+        (
+            "Workspaces",
+            vec![
+                "workspace",
+                "movetoworkspace",
+                "movetoworkspacesilent",
+                "togglespecialworkspace",
+                "movecurrentworkspacetomonitor",
+                "swapactiveworkspaces",
+            ],
+        ),
+        (
+            "Session",
+            vec!["exit", "dpms", "submap", "pass", "sendshortcut"],
+        ),
+    ]
+}
+
+// Renamed and removed Hyprland options, each applied once the installed Hyprland is at least `since`
+pub fn get_migrations() -> Vec<Migration> {
+    use MigrationAction::*;
+
+    let migration = |since: Version, key: &'static str, action: MigrationAction| Migration {
+        since,
+        key,
+        action,
+    };
+
+    vec![
+        // Cursor options got their own category
+        migration(
+            Version::new(0, 37, 0),
+            "general:cursor_inactive_timeout",
+            Rename("cursor:inactive_timeout"),
+        ),
+        migration(
+            Version::new(0, 37, 0),
+            "general:no_cursor_warps",
+            Rename("cursor:no_warps"),
+        ),
+        migration(
+            Version::new(0, 41, 0),
+            "master:new_is_master",
+            Convert("master:new_status", |value| match value {
+                "true" | "yes" | "on" | "1" => Some(String::from("master")),
+                _ => Some(String::from("slave")),
+            }),
+        ),
+        migration(
+            Version::new(0, 42, 0),
+            "misc:no_direct_scanout",
+            Convert("render:direct_scanout", |value| match value {
+                "true" | "yes" | "on" | "1" => Some(String::from("false")),
+                _ => Some(String::from("true")),
+            }),
+        ),
+        // Shadow options moved into `decoration:shadow`
+        migration(
+            Version::new(0, 45, 0),
+            "decoration:drop_shadow",
+            Rename("decoration:shadow:enabled"),
+        ),
+        migration(
+            Version::new(0, 45, 0),
+            "decoration:shadow_range",
+            Rename("decoration:shadow:range"),
+        ),
+        migration(
+            Version::new(0, 45, 0),
+            "decoration:shadow_render_power",
+            Rename("decoration:shadow:render_power"),
+        ),
+        migration(
+            Version::new(0, 45, 0),
+            "decoration:shadow_offset",
+            Rename("decoration:shadow:offset"),
+        ),
+        migration(
+            Version::new(0, 45, 0),
+            "decoration:shadow_scale",
+            Rename("decoration:shadow:scale"),
+        ),
+        migration(
+            Version::new(0, 45, 0),
+            "decoration:shadow_ignore_window",
+            Rename("decoration:shadow:ignore_window"),
+        ),
+        migration(
+            Version::new(0, 45, 0),
+            "decoration:col.shadow",
+            Rename("decoration:shadow:color"),
+        ),
+        migration(
+            Version::new(0, 45, 0),
+            "decoration:col.shadow_inactive",
+            Rename("decoration:shadow:color_inactive"),
+        ),
+        // Replaced by workspace rules
+        migration(Version::new(0, 45, 0), "dwindle:no_gaps_when_only", Remove),
+        migration(Version::new(0, 45, 0), "master:no_gaps_when_only", Remove),
+        // The old `windowrule = RULE, REGEX` syntax was dropped and `windowrulev2` took over its name
+        migration(
+            Version::new(0, 48, 0),
+            "windowrule",
+            Convert("windowrule", |value| {
+                let (rule, window) = value.split_once(',')?;
+                let window: &str = window.trim();
+
+                // `title:` means the same in both syntaxes, a bare regex used to match the class
+                let has_field: bool = get_window_rule_fields()
+                    .iter()
+                    .any(|field| window.starts_with(field));
+
+                match has_field {
+                    true => Some(value.to_string()),
+                    false => Some(format!("{}, class:{}", rule.trim(), window)),
+                }
+            }),
+        ),
+        migration(Version::new(0, 48, 0), "windowrulev2", Rename("windowrule")),
+        // Workspace swiping is configured through `gesture` lines
+        migration(
+            Version::new(0, 51, 0),
+            "gestures:workspace_swipe",
+            Convert("gesture", |value| match value {
+                "true" | "yes" | "on" | "1" => Some(String::from("3, horizontal, workspace")),
+                _ => None,
+            }),
+        ),
+        migration(
+            Version::new(0, 51, 0),
+            "gestures:workspace_swipe_fingers",
+            Remove,
+        ),
+    ]
+}
+
+// Programs configured by the dotfiles together with the config directory they own
+pub fn get_programs() -> Vec<Program> {
+    vec![
+        Program {
+            name: "Hyprland",
+            executable: "Hyprland",
+            package: "hyprland",
+            config_dir: "hypr",
+            version_arg: Some("--version"),
+        },
+        Program {
+            name: "Kitty",
+            executable: "kitty",
+            package: "kitty",
+            config_dir: "kitty",
+            version_arg: Some("--version"),
+        },
+        Program {
+            name: "Neovim",
+            executable: "nvim",
+            package: "neovim",
+            config_dir: "nvim",
+            version_arg: Some("--version"),
+        },
+        Program {
+            name: "Waybar",
+            executable: "waybar",
+            package: "waybar",
+            config_dir: "waybar",
+            version_arg: Some("--version"),
+        },
+        Program {
+            name: "Wofi",
+            executable: "wofi",
+            package: "wofi",
+            config_dir: "wofi",
+            version_arg: Some("--version"),
+        },
+        Program {
+            name: "Yazi",
+            executable: "yazi",
+            package: "yazi",
+            config_dir: "yazi",
+            version_arg: Some("--version"),
+        },
+        Program {
+            name: "Zsh",
+            executable: "zsh",
+            package: "zsh",
+            config_dir: "zsh",
+            version_arg: Some("--version"),
+        },
+    ]
+}
+
+// Fields a `windowrule` can match windows by since Hyprland 0.48
+pub fn get_window_rule_fields() -> Vec<&'static str> {
+    vec![
+        "class:",
+        "title:",
+        "initialClass:",
+        "initialTitle:",
+        "tag:",
+        "xwayland:",
+        "floating:",
+        "fullscreen:",
+        "pinned:",
+        "focus:",
+        "group:",
+        "fullscreenstate:",
+        "workspace:",
+        "onworkspace:",
+        "content:",
+        "xdgtag:",
+        "modal:",
+    ]
+}
+
+// Packages the dotfiles depend on, which are the optional dependencies of the AUR package
+pub fn get_dependencies() -> Vec<String> {
+    parse_optdepends(include_str!("../../Cargo.toml"))
+}
+
+// Plugins and flavors installed for yazi with `ya pack`
+pub fn get_yazi_packages() -> Vec<&'static str> {
+    vec![
+        "dedukun/bookmarks",
+        "dedukun/relative-motions",
+        "yazi-rs/flavors:catppuccin-macchiato",
+        "yazi-rs/plugins:full-border",
+    ]
+}
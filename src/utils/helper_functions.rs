@@ -1,10 +1,15 @@
-use crate::{prompt, success, utils::contents::get_kb_layouts, KBLayout, Paths, UserInput};
+use crate::{
+    prompt, success, utils::pacman::find_package, Executable, Package, Paths, Program,
+    ProgramStatus, UserInput,
+};
 use colored::Colorize;
 use std::{
-    collections::BTreeMap,
+    env,
+    ffi::OsStr,
     fs::{self, DirEntry, FileType},
     io::{self, stdin, stdout, Read, Write},
-    path::Path,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
     process::Command,
 };
 
@@ -17,6 +22,16 @@ pub fn read_input() -> io::Result<String> {
     Ok(input.trim().to_lowercase())
 }
 
+// Like `read_input`, but keeps the case for commands, paths and XKB names
+pub fn read_raw_input() -> io::Result<String> {
+    let mut input: String = String::new();
+
+    io::stdout().flush()?;
+    io::stdin().read_line(&mut input)?;
+
+    Ok(input.trim().to_string())
+}
+
 pub fn parse_input(input: &str) -> UserInput {
     match input {
         "y" | "yes" => UserInput::Yes,
@@ -50,7 +65,11 @@ pub fn copy_recursively(src: impl AsRef<Path>, dest: impl AsRef<Path>) -> io::Re
     Ok(())
 }
 
-pub fn cleanup<'a>(paths: &Paths, entries_to_delete: impl AsRef<[&'a str]>) -> io::Result<()> {
+pub fn cleanup<'a>(
+    paths: &Paths,
+    entries_to_delete: impl AsRef<[&'a str]>,
+    skipped: &[String],
+) -> io::Result<()> {
     for entry in fs::read_dir(&paths.repo)? {
         let entry: DirEntry = entry?;
         let filetype: FileType = entry.file_type()?;
@@ -65,7 +84,7 @@ pub fn cleanup<'a>(paths: &Paths, entries_to_delete: impl AsRef<[&'a str]>) -> i
             success!("==> Successfully set wallpaper in ~/Documents/wallpapers");
         }
 
-        if entry.file_name() == "zsh" {
+        if entry.file_name() == "zsh" && !skipped.iter().any(|dir| dir == "zsh") {
             fs::copy(entry.path().join(".zshrc"), paths.home.join(".zshrc"))?;
 
             success!("==> Copied .zshrc to ~/.zshrc before removing zsh directory");
@@ -99,33 +118,71 @@ pub fn cleanup<'a>(paths: &Paths, entries_to_delete: impl AsRef<[&'a str]>) -> i
     Ok(())
 }
 
-pub fn get_kb_layout_code() -> io::Result<KBLayout> {
-    let mut input: String;
-    let kb_layouts: BTreeMap<&str, &str> = get_kb_layouts();
-
-    loop {
-        prompt!("Please enter a valid keyboard layout. Press l to see a [l]ist of available options or q to [q]uit:");
-
-        input = read_input()?;
-
-        match input.as_str() {
-            "q" | "quit" => return Ok(KBLayout::Default),
-            "l" | "list" => kb_layouts
-                .iter()
-                .for_each(|(code, lang)| println!("{} -> {}", *code, *lang)),
-            _ => {
-                if kb_layouts.iter().any(|(&code, _)| *code == input) {
-                    break;
-                } else {
-                    continue;
-                }
-            }
-        }
+// Resolves a command the same way `which` does: by scanning every directory inside $PATH for an executable file
+pub fn find_executable(command: &str) -> Option<PathBuf> {
+    find_executable_in(command, &env::var_os("PATH")?)
+}
+
+// Same as `find_executable`, but searches the given list of directories instead of $PATH
+pub fn find_executable_in(command: &str, search_path: &OsStr) -> Option<PathBuf> {
+    if command.contains('/') {
+        let path: &Path = Path::new(command);
+        return is_executable(path).then(|| path.to_path_buf());
     }
 
-    Ok(KBLayout::Change(input))
+    env::split_paths(search_path)
+        .map(|dir| dir.join(command))
+        .find(|path| is_executable(path))
+}
+
+fn is_executable(path: &Path) -> bool {
+    fs::metadata(path)
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
 }
 
-pub fn command_exists(command: &str) -> bool {
-    Command::new(command).arg("-v").output().is_ok()
+// Looks up a command and, if a version argument is given, runs it to get the first line of its version output
+pub fn lookup_executable(command: &str, version_arg: Option<&str>) -> Option<Executable> {
+    let path: PathBuf = find_executable(command)?;
+
+    let version: Option<String> = version_arg.and_then(|arg| {
+        let output = Command::new(&path).arg(arg).output().ok()?;
+
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(String::from)
+    });
+
+    Some(Executable { path, version })
+}
+
+// `packages` is read once by the caller, so checking many programs doesn't re-read the pacman database
+pub fn program_status(program: &Program, packages: &[Package]) -> ProgramStatus {
+    if let Some(executable) = lookup_executable(program.executable, program.version_arg) {
+        ProgramStatus::Installed(Some(executable))
+    } else if find_package(packages, program.package).is_some() {
+        ProgramStatus::Installed(None)
+    } else {
+        ProgramStatus::NotInstalled
+    }
+}
+
+// Picks the first available package helper, preferring AUR helpers over plain pacman
+pub fn detect_package_helper() -> Option<(&'static str, PathBuf)> {
+    detect_package_helper_in(&env::var_os("PATH")?)
+}
+
+pub fn detect_package_helper_in(search_path: &OsStr) -> Option<(&'static str, PathBuf)> {
+    ["paru", "yay", "pacman"]
+        .into_iter()
+        .find_map(|helper| find_executable_in(helper, search_path).map(|path| (helper, path)))
+}
+
+// Expands a leading `~` to the home directory
+pub fn expand_home(path: &str, home: &Path) -> PathBuf {
+    match path.strip_prefix('~') {
+        Some(rest) => home.join(rest.trim_start_matches('/')),
+        None => PathBuf::from(path),
+    }
 }
@@ -1,4 +1,6 @@
+pub mod answers;
 pub mod contents;
 pub mod helper_functions;
 pub mod macros;
+pub mod pacman;
 pub mod types;
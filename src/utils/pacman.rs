@@ -0,0 +1,175 @@
+use crate::Package;
+use std::{
+    fs::{self, DirEntry},
+    io,
+    path::Path,
+};
+
+// Reads all installed packages from a pacman local database (usually /var/lib/pacman/local)
+pub fn installed_packages(pacman_db: &Path) -> io::Result<Vec<Package>> {
+    let mut packages: Vec<Package> = Vec::new();
+
+    for entry in fs::read_dir(pacman_db)? {
+        let entry: DirEntry = entry?;
+
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+
+        let Ok(desc) = fs::read_to_string(entry.path().join("desc")) else {
+            continue;
+        };
+
+        if let Some(package) = parse_desc(&desc) {
+            packages.push(package);
+        }
+    }
+
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(packages)
+}
+
+// Parses a `desc` file which consists of `%SECTION%` headers followed by one value per line
+pub fn parse_desc(desc: &str) -> Option<Package> {
+    let mut name: Option<String> = None;
+    let mut version: Option<String> = None;
+    let mut provides: Vec<String> = Vec::new();
+    let mut section: &str = "";
+
+    for line in desc.lines().map(str::trim) {
+        if line.is_empty() {
+            section = "";
+            continue;
+        }
+
+        if line.starts_with('%') && line.ends_with('%') {
+            section = line;
+            continue;
+        }
+
+        match section {
+            "%NAME%" => name = Some(line.to_string()),
+            "%VERSION%" => version = Some(line.to_string()),
+            // Provided names may carry a version like `sh=5.2`
+            "%PROVIDES%" => provides.push(line.split('=').next().unwrap_or(line).to_string()),
+            _ => {}
+        }
+    }
+
+    Some(Package {
+        name: name?,
+        version: version?,
+        provides,
+    })
+}
+
+pub fn find_package<'a>(packages: &'a [Package], name: &str) -> Option<&'a Package> {
+    packages
+        .iter()
+        .find(|package| package.name == name || package.provides.iter().any(|p| p == name))
+}
+
+// Returns the packages from `required` which are not installed
+pub fn missing_packages<'a>(packages: &[Package], required: &[&'a str]) -> Vec<&'a str> {
+    required
+        .iter()
+        .copied()
+        .filter(|name| find_package(packages, name).is_none())
+        .collect()
+}
+
+// Reads the `optdepends` of the `[package.metadata.aur]` section of a Cargo manifest
+pub fn parse_optdepends(manifest: &str) -> Vec<String> {
+    let Some((_, section)) = manifest.split_once("[package.metadata.aur]") else {
+        return Vec::new();
+    };
+    let section: &str = section.split("\n[").next().unwrap_or_default();
+
+    let Some((_, list)) = section
+        .split_once("optdepends")
+        .and_then(|(_, value)| value.split_once('['))
+    else {
+        return Vec::new();
+    };
+
+    // Entries may carry a reason like `zsh: default shell`
+    list.split(']')
+        .next()
+        .unwrap_or_default()
+        .split(',')
+        .map(|item| item.trim().trim_matches('"'))
+        .map(|item| item.split(':').next().unwrap_or(item).trim())
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESC: &str = "\
+%NAME%
+neovim
+
+%VERSION%
+0.10.4-1
+
+%DESC%
+Fork of Vim aiming to improve user experience, plugins, and GUIs
+
+%DEPENDS%
+libuv
+luajit>=2.1
+
+%PROVIDES%
+vim-plugin-runtime
+nvim=0.10.4
+";
+
+    #[test]
+    fn parses_name_version_and_provides() {
+        let package: Package = parse_desc(DESC).unwrap();
+
+        assert_eq!(package.name, "neovim");
+        assert_eq!(package.version, "0.10.4-1");
+        assert_eq!(package.provides, ["vim-plugin-runtime", "nvim"]);
+
+        let packages: Vec<Package> = vec![package];
+
+        assert!(find_package(&packages, "neovim").is_some());
+        assert!(find_package(&packages, "nvim").is_some());
+        assert!(find_package(&packages, "luajit").is_none());
+        assert_eq!(
+            missing_packages(&packages, &["nvim", "neovim", "vim"]),
+            ["vim"]
+        );
+    }
+
+    #[test]
+    fn rejects_desc_without_name_or_version() {
+        assert!(parse_desc("%NAME%\nneovim\n").is_none());
+        assert!(parse_desc("%VERSION%\n0.10.4-1\n").is_none());
+    }
+
+    #[test]
+    fn reads_optdepends_of_the_aur_section() {
+        let manifest: &str = "\
+[package]
+name = \"autoricer\"
+
+[package.metadata.aur]
+optdepends = [
+    \"hyprland\",
+    \"zsh: default shell\",
+]
+
+[dependencies]
+regex = \"1.10.2\"
+";
+
+        assert_eq!(parse_optdepends(manifest), ["hyprland", "zsh"]);
+        assert!(parse_optdepends("[package]\nname = \"autoricer\"\n").is_empty());
+    }
+}
@@ -1,4 +1,6 @@
-use std::path::PathBuf;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::{collections::BTreeMap, env, fmt, path::PathBuf};
 
 pub enum UserInput {
     Yes,
@@ -27,11 +29,530 @@ pub enum HyprConfig {
     Default,
 }
 
+pub enum ReloadStatus {
+    Reloaded,
+    NotRunning,
+    ConfigErrors(Vec<String>),
+}
+
 pub enum KBLayout {
-    Change(String),
+    Change(KeyboardConfig),
     Default,
 }
 
+pub struct KeyboardConfig {
+    pub layouts: Vec<String>,
+    // One variant per layout, an empty string selects the default variant
+    pub variants: Vec<String>,
+    pub model: String,
+    pub options: Vec<String>,
+}
+
+pub struct DetectedKeyboard {
+    pub keyboard: KeyboardConfig,
+    // File the layout was read from
+    pub source: PathBuf,
+}
+
+impl KeyboardConfig {
+    // Builds the config from comma separated values as stored in config files
+    pub fn from_settings(layout: &str, variant: &str, model: &str, options: &str) -> Self {
+        let split = |value: &str| -> Vec<String> {
+            value
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .collect()
+        };
+
+        let mut variants: Vec<String> = split(variant);
+
+        while variants.last().is_some_and(|variant| variant.is_empty()) {
+            variants.pop();
+        }
+
+        Self {
+            layouts: split(layout),
+            variants,
+            model: model.trim().to_string(),
+            options: split(options)
+                .into_iter()
+                .filter(|option| !option.is_empty())
+                .collect(),
+        }
+    }
+
+    // Short description like `de nodeadkeys, us`
+    pub fn describe(&self) -> String {
+        self.layouts
+            .iter()
+            .enumerate()
+            .map(
+                |(index, layout)| match self.variants.get(index).filter(|v| !v.is_empty()) {
+                    Some(variant) => format!("{} {}", layout, variant),
+                    None => layout.clone(),
+                },
+            )
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    // Settings inside the `input` section of the Hyprland config
+    pub fn hypr_settings(&self) -> [(&'static str, String); 4] {
+        [
+            ("input:kb_layout", self.layouts.join(",")),
+            ("input:kb_variant", self.variants.join(",")),
+            ("input:kb_model", self.model.clone()),
+            ("input:kb_options", self.options.join(",")),
+        ]
+    }
+}
+
+pub struct XkbItem {
+    pub name: String,
+    pub description: String,
+}
+
+pub struct XkbLayout {
+    pub name: String,
+    pub description: String,
+    pub variants: Vec<XkbItem>,
+}
+
+pub struct XkbRules {
+    pub models: Vec<XkbItem>,
+    pub layouts: Vec<XkbLayout>,
+    pub options: Vec<XkbItem>,
+}
+
+impl XkbRules {
+    pub fn layout(&self, name: &str) -> Option<&XkbLayout> {
+        self.layouts.iter().find(|layout| layout.name == name)
+    }
+
+    // Without data from evdev.xml, variants, models and options can't be checked
+    pub fn has_variant(&self, layout: &str, variant: &str) -> bool {
+        self.layout(layout).is_some_and(|layout| {
+            layout.variants.is_empty() || layout.variants.iter().any(|v| v.name == variant)
+        })
+    }
+
+    pub fn has_model(&self, model: &str) -> bool {
+        self.models.is_empty() || self.models.iter().any(|m| m.name == model)
+    }
+
+    pub fn has_option(&self, option: &str) -> bool {
+        self.options.is_empty() || self.options.iter().any(|o| o.name == option)
+    }
+}
+
+pub enum GpuVendor {
+    Nvidia,
+    Amd,
+    Intel,
+    Other(String),
+}
+
+impl GpuVendor {
+    // Vendor IDs as found in /sys/bus/pci/devices/*/vendor
+    pub fn from_pci_id(id: &str) -> Self {
+        match id {
+            "0x10de" => GpuVendor::Nvidia,
+            "0x1002" => GpuVendor::Amd,
+            "0x8086" => GpuVendor::Intel,
+            _ => GpuVendor::Other(id.to_string()),
+        }
+    }
+}
+
+impl GpuVendor {
+    // Lowercase name used for file names
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            GpuVendor::Nvidia => "nvidia",
+            GpuVendor::Amd => "amd",
+            GpuVendor::Intel => "intel",
+            GpuVendor::Other(_) => "gpu",
+        }
+    }
+}
+
+impl fmt::Display for GpuVendor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GpuVendor::Nvidia => write!(f, "NVIDIA"),
+            GpuVendor::Amd => write!(f, "AMD"),
+            GpuVendor::Intel => write!(f, "Intel"),
+            GpuVendor::Other(id) => write!(f, "unknown ({})", id),
+        }
+    }
+}
+
+pub struct Gpu {
+    pub pci_address: String,
+    pub vendor: GpuVendor,
+}
+
+pub struct DrmCard {
+    // Name below /sys/class/drm, e.g. `card1`
+    pub name: String,
+    pub pci_address: String,
+    pub vendor: GpuVendor,
+    // Whether the firmware used this card for the boot display
+    pub boot_vga: bool,
+    // Stable device path, `/dev/dri/cardN` numbers can change between boots
+    pub by_path: PathBuf,
+}
+
+pub struct Monitor {
+    // Connector name Hyprland uses, e.g. `eDP-1` or `DP-2`
+    pub name: String,
+    pub manufacturer: String,
+    pub model: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate: f64,
+    pub width_mm: u32,
+    pub height_mm: u32,
+}
+
+pub struct LaptopReport {
+    pub batteries: Vec<String>,
+    pub backlights: Vec<String>,
+}
+
+impl LaptopReport {
+    pub fn is_laptop(&self) -> bool {
+        !self.batteries.is_empty()
+    }
+}
+
+pub enum InputDeviceKind {
+    Touchpad,
+    Mouse,
+}
+
+pub struct InputDevice {
+    pub name: String,
+    pub kind: InputDeviceKind,
+}
+
+pub enum NvidiaDriver {
+    Proprietary,
+    Nouveau,
+    None,
+}
+
+pub struct GpuReport {
+    pub gpus: Vec<Gpu>,
+    pub nvidia_driver: NvidiaDriver,
+    // Explanation shown to the user why the NVIDIA question was pre-answered
+    pub reasons: Vec<String>,
+}
+
+impl GpuReport {
+    // The NVIDIA environment variables are only needed with the proprietary driver
+    pub fn needs_nvidia_env_vars(&self) -> bool {
+        self.gpus
+            .iter()
+            .any(|gpu| matches!(gpu.vendor, GpuVendor::Nvidia))
+            && !matches!(self.nvidia_driver, NvidiaDriver::Nouveau)
+    }
+}
+
+pub struct EnvGroup {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub vars: Vec<(&'static str, &'static str)>,
+}
+
+pub enum EnvGroupStatus {
+    Enabled,
+    Partial,
+    Disabled,
+}
+
+pub enum ProgramStatus {
+    Installed(Option<Executable>),
+    NotInstalled,
+}
+
+pub enum PackageInstall {
+    Installed,
+    Skipped,
+}
+
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub details: String,
+}
+
+#[derive(Clone, Copy)]
+pub enum HyprOptionType {
+    Bool,
+    Int,
+    // One to four integers like CSS margins, e.g. `gaps_out = 5,10,5,10`
+    Gaps,
+    Float,
+    String,
+}
+
+impl HyprOptionType {
+    pub fn accepts(&self, value: &str) -> bool {
+        match self {
+            Self::Bool => matches!(
+                value,
+                "true" | "false" | "yes" | "no" | "on" | "off" | "0" | "1"
+            ),
+            Self::Int => value.parse::<i64>().is_ok(),
+            Self::Gaps => {
+                let gaps: Vec<&str> = value.split(',').map(str::trim).collect();
+
+                gaps.len() <= 4 && gaps.iter().all(|gap| gap.parse::<i64>().is_ok())
+            }
+            Self::Float => value.parse::<f64>().is_ok(),
+            Self::String => true,
+        }
+    }
+}
+
+impl fmt::Display for HyprOptionType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Bool => write!(f, "a boolean"),
+            Self::Int => write!(f, "an integer"),
+            Self::Gaps => write!(f, "one to four integers"),
+            Self::Float => write!(f, "a number"),
+            Self::String => write!(f, "a string"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    // Finds the first `x.y.z` inside a string like `Hyprland 0.45.2 built from branch main`
+    pub fn parse(text: &str) -> Option<Self> {
+        static VERSION_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\d+)\.(\d+)\.(\d+)").unwrap());
+
+        let captures: Captures = VERSION_RE.captures(text)?;
+
+        Some(Self::new(
+            captures[1].parse().ok()?,
+            captures[2].parse().ok()?,
+            captures[3].parse().ok()?,
+        ))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+pub enum MigrationAction {
+    // Moves the value to a new key
+    Rename(&'static str),
+    // Moves the value to a new key after converting it; `None` removes the line
+    Convert(&'static str, fn(&str) -> Option<String>),
+    Remove,
+}
+
+pub struct Migration {
+    pub since: Version,
+    pub key: &'static str,
+    pub action: MigrationAction,
+}
+
+pub struct Rewrite {
+    pub since: Version,
+    pub file: PathBuf,
+    pub before: String,
+    pub after: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct Bind {
+    // Letters after `bind`, e.g. `e` for `binde` or `m` for mouse binds
+    pub flags: String,
+    pub mods: Vec<String>,
+    pub key: String,
+    pub dispatcher: String,
+    pub arg: String,
+    pub description: Option<String>,
+    pub submap: Option<String>,
+}
+
+impl Bind {
+    // Modifiers followed by the key
+    pub fn keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self.mods.iter().map(String::as_str).collect();
+        keys.push(&self.key);
+
+        keys
+    }
+
+    // Key combination as it is pressed, e.g. `SUPER + SHIFT + Q`
+    pub fn combo(&self) -> String {
+        self.keys().join(" + ")
+    }
+
+    // Binds are triggered by the same keys if they share modifiers, key, submap and the flags deciding when they fire
+    pub fn same_keys(&self, other: &Bind) -> bool {
+        self.mods == other.mods
+            && self.key == other.key
+            && self.submap == other.submap
+            && self.trigger_flags() == other.trigger_flags()
+    }
+
+    // Release, long press, locked, mouse, click and drag binds don't fire together with a plain bind
+    fn trigger_flags(&self) -> Vec<char> {
+        let mut flags: Vec<char> = self
+            .flags
+            .chars()
+            .filter(|flag| "rolmcg".contains(*flag))
+            .collect();
+        flags.sort_unstable();
+
+        flags
+    }
+
+    pub fn same_action(&self, other: &Bind) -> bool {
+        self.dispatcher == other.dispatcher
+            && self.arg.split_whitespace().eq(other.arg.split_whitespace())
+    }
+
+    // The bind written back as a Hyprland line with all variables already expanded
+    pub fn to_line(&self) -> String {
+        let mut fields: Vec<&str> = vec![];
+        let mods: String = self.mods.join(" ");
+
+        fields.push(&mods);
+        fields.push(&self.key);
+
+        if self.flags.contains('d') {
+            fields.push(self.description.as_deref().unwrap_or_default());
+        }
+
+        fields.push(&self.dispatcher);
+
+        // Dispatchers like `killactive` take no argument
+        if !self.arg.is_empty() {
+            fields.push(&self.arg);
+        }
+
+        format!("bind{} = {}", self.flags, fields.join(", "))
+    }
+
+    // What the bind does: its description if it has one, otherwise the dispatcher and argument
+    pub fn action(&self) -> String {
+        match (
+            &self.description,
+            self.dispatcher.as_str(),
+            self.arg.as_str(),
+        ) {
+            (Some(description), _, _) => description.clone(),
+            (None, dispatcher, "") => dispatcher.to_string(),
+            (None, "exec", arg) => arg.to_string(),
+            (None, dispatcher, arg) => format!("{} {}", dispatcher, arg),
+        }
+    }
+}
+
+pub struct BindConflicts {
+    // Keys bound in both configs, but to different actions, as (old, new)
+    pub changed: Vec<(Bind, Bind)>,
+    // Keys bound more than once in the new config
+    pub collisions: Vec<(Bind, Bind)>,
+    // Keys only bound in the old config
+    pub user_only: Vec<Bind>,
+}
+
+pub struct AutostartEntry {
+    pub file: PathBuf,
+    pub line: usize,
+    pub command: String,
+    // Commented out entries are disabled
+    pub enabled: bool,
+}
+
+pub struct AppVariable {
+    // Variable in the Hypr config without the leading `$`
+    pub name: &'static str,
+    pub description: &'static str,
+    // Suggested when installed
+    pub candidates: Vec<&'static str>,
+}
+
+pub enum CheatsheetFormat {
+    Terminal,
+    Markdown,
+    Html,
+}
+
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+pub struct ConfigIssue {
+    pub severity: Severity,
+    pub file: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+pub struct Program {
+    pub name: &'static str,
+    pub executable: &'static str,
+    pub package: &'static str,
+    pub config_dir: &'static str,
+    pub version_arg: Option<&'static str>,
+}
+
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    pub provides: Vec<String>,
+}
+
+pub struct Executable {
+    pub path: PathBuf,
+    pub version: Option<String>,
+}
+
+// Answers given with `--answers FILE` instead of typing them into the prompts
+#[derive(Default)]
+pub struct Answers {
+    pub values: BTreeMap<String, String>,
+}
+
+impl Answers {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+}
+
 pub struct Paths {
     pub home: PathBuf,
     pub config: PathBuf,
@@ -39,6 +560,11 @@ pub struct Paths {
     pub repo: PathBuf,
     pub hypr_config: PathBuf,
     pub wallpapers: PathBuf,
+    pub pacman_db: PathBuf,
+    pub xkb_rules: PathBuf,
+    pub etc: PathBuf,
+    pub sys: PathBuf,
+    pub proc: PathBuf,
 }
 
 impl Paths {
@@ -56,6 +582,14 @@ impl Paths {
             wallpapers: dirs::document_dir()
                 .expect("Cannot get ~/Documents path")
                 .join("wallpapers"),
+            pacman_db: PathBuf::from("/var/lib/pacman/local"),
+            // Same override libxkbcommon uses
+            xkb_rules: env::var_os("XKB_CONFIG_ROOT")
+                .map_or_else(|| PathBuf::from("/usr/share/X11/xkb"), PathBuf::from)
+                .join("rules/evdev.xml"),
+            etc: PathBuf::from("/etc"),
+            sys: PathBuf::from("/sys"),
+            proc: PathBuf::from("/proc"),
         }
     }
 }
@@ -0,0 +1,299 @@
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+static MODULES_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?s)("modules-(?:left|center|right)"\s*:\s*\[)([^\]]*)(\])"#).unwrap()
+});
+static ITEM_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#""([^"]*)""#).unwrap());
+
+// Waybar reads `config` or `config.jsonc` from its config directory
+pub fn waybar_config(config_dir: &Path) -> Option<PathBuf> {
+    ["config", "config.jsonc"]
+        .iter()
+        .map(|name| config_dir.join("waybar").join(name))
+        .find(|path| path.exists())
+}
+
+// Removes modules like `battery` or `battery#bat2` from all module lists, returning the removed ones
+pub fn remove_waybar_modules(config: &Path, modules: &[&str]) -> io::Result<Vec<String>> {
+    let contents: String = fs::read_to_string(config)?;
+    let mut removed: Vec<String> = Vec::new();
+
+    let updated = MODULES_RE.replace_all(&contents, |captures: &Captures| {
+        if has_comments(&captures[2]) {
+            return captures[0].to_string();
+        }
+
+        let items: Vec<&str> = split_items(&captures[2]);
+        let (kept, dropped): (Vec<&str>, Vec<&str>) = items
+            .into_iter()
+            .partition(|item| !modules.contains(&module_type(item)));
+
+        removed.extend(
+            dropped
+                .iter()
+                .map(|item| item.trim_matches('"').to_string()),
+        );
+
+        format!(
+            "{}{}{}",
+            &captures[1],
+            join_items(&captures[2], &kept),
+            &captures[3]
+        )
+    });
+
+    if !removed.is_empty() {
+        fs::write(config, updated.as_ref())?;
+    }
+
+    Ok(removed)
+}
+
+// Adds modules which are configured but not shown to the end of `modules-right`
+pub fn add_waybar_modules(config: &Path, modules: &[&str]) -> io::Result<Vec<String>> {
+    let contents: String = fs::read_to_string(config)?;
+
+    let listed: Vec<String> = MODULES_RE
+        .captures_iter(&contents)
+        .flat_map(|captures| {
+            // Only the quoted names count, so modules next to comments are found as well
+            ITEM_RE
+                .captures_iter(captures.get(2).map_or("", |m| m.as_str()))
+                .map(|item| module_type(&item[1]).to_string())
+                .collect::<Vec<String>>()
+        })
+        .collect();
+
+    let added: Vec<String> = modules
+        .iter()
+        .filter(|module| !listed.iter().any(|listed| listed == *module))
+        .filter(|module| {
+            Regex::new(&format!(
+                r#""{}(#[^"]*)?"\s*:\s*\{{"#,
+                regex::escape(module)
+            ))
+            .unwrap()
+            .is_match(&contents)
+        })
+        .map(|module| module.to_string())
+        .collect();
+
+    if added.is_empty() {
+        return Ok(added);
+    }
+
+    let mut inserted: bool = false;
+
+    let updated = MODULES_RE.replace_all(&contents, |captures: &Captures| {
+        if !captures[1].contains("modules-right") || has_comments(&captures[2]) {
+            return captures[0].to_string();
+        }
+
+        inserted = true;
+
+        let mut items: Vec<String> = split_items(&captures[2])
+            .into_iter()
+            .map(String::from)
+            .collect();
+        items.extend(added.iter().map(|module| format!("\"{}\"", module)));
+
+        let items: Vec<&str> = items.iter().map(String::as_str).collect();
+
+        format!(
+            "{}{}{}",
+            &captures[1],
+            join_items(&captures[2], &items),
+            &captures[3]
+        )
+    });
+
+    // Nothing was added if there is no `modules-right` or it has comments in it
+    if !inserted {
+        return Ok(Vec::new());
+    }
+
+    fs::write(config, updated.as_ref())?;
+
+    Ok(added)
+}
+
+// Lists with comments in them are left alone instead of guessing where items start
+fn has_comments(list: &str) -> bool {
+    list.contains("//") || list.contains("/*")
+}
+
+fn split_items(list: &str) -> Vec<&str> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+// `"battery#bat2"` is an instance of the `battery` module
+fn module_type(item: &str) -> &str {
+    let name: &str = item.trim_matches('"');
+
+    name.split('#').next().unwrap_or(name)
+}
+
+// Writes the items back in the layout of the original list, either one per line or on a single line
+fn join_items(original: &str, items: &[&str]) -> String {
+    if !original.contains('\n') {
+        return items.join(", ");
+    }
+
+    let indent: &str = original
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .unwrap_or("    ");
+    let closing: &str = original.rsplit('\n').next().unwrap_or_default();
+
+    let lines: Vec<String> = items
+        .iter()
+        .map(|item| format!("\n{}{}", indent, item))
+        .collect();
+
+    format!("{}\n{}", lines.join(","), closing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+
+    fn write_config(name: &str, contents: &str) -> PathBuf {
+        let dir: PathBuf =
+            env::temp_dir().join(format!("autoricer-waybar-{}-{}", name, process::id()));
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("config.jsonc"), contents).unwrap();
+
+        dir.join("config.jsonc")
+    }
+
+    fn remove_config(config: &Path) {
+        fs::remove_dir_all(config.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn edits_single_line_lists() {
+        let config: PathBuf = write_config(
+            "single",
+            "{\n    \"modules-right\": [\"pulseaudio\", \"battery\", \"clock\"],\n    \"backlight\": {}\n}\n",
+        );
+
+        let removed: Vec<String> = remove_waybar_modules(&config, &["battery"]).unwrap();
+        assert_eq!(removed, vec!["battery"]);
+
+        let added: Vec<String> = add_waybar_modules(&config, &["backlight"]).unwrap();
+        assert_eq!(added, vec!["backlight"]);
+
+        assert_eq!(
+            fs::read_to_string(&config).unwrap(),
+            "{\n    \"modules-right\": [\"pulseaudio\", \"clock\", \"backlight\"],\n    \"backlight\": {}\n}\n"
+        );
+
+        remove_config(&config);
+    }
+
+    #[test]
+    fn edits_multi_line_lists() {
+        let config: PathBuf = write_config(
+            "multi",
+            "\
+{
+    \"modules-right\": [
+        \"pulseaudio\",
+        \"clock\"
+    ],
+    \"battery\": {
+        \"format\": \"{capacity}%\"
+    }
+}
+",
+        );
+
+        // Modules without a configuration block aren't added
+        let added: Vec<String> = add_waybar_modules(&config, &["battery", "backlight"]).unwrap();
+        assert_eq!(added, vec!["battery"]);
+
+        assert_eq!(
+            fs::read_to_string(&config).unwrap(),
+            "\
+{
+    \"modules-right\": [
+        \"pulseaudio\",
+        \"clock\",
+        \"battery\"
+    ],
+    \"battery\": {
+        \"format\": \"{capacity}%\"
+    }
+}
+"
+        );
+
+        let removed: Vec<String> = remove_waybar_modules(&config, &["pulseaudio"]).unwrap();
+        assert_eq!(removed, vec!["pulseaudio"]);
+        assert!(fs::read_to_string(&config)
+            .unwrap()
+            .contains("[\n        \"clock\",\n        \"battery\"\n    ]"));
+
+        remove_config(&config);
+    }
+
+    #[test]
+    fn handles_module_instances() {
+        let config: PathBuf = write_config(
+            "instances",
+            "{\n    \"modules-left\": [\"battery#bat2\"],\n    \"modules-right\": [\"clock\"],\n    \"battery#bat2\": {}\n}\n",
+        );
+
+        // `battery#bat2` already shows a battery
+        let added: Vec<String> = add_waybar_modules(&config, &["battery"]).unwrap();
+        assert!(added.is_empty());
+
+        let removed: Vec<String> = remove_waybar_modules(&config, &["battery"]).unwrap();
+        assert_eq!(removed, vec!["battery#bat2"]);
+
+        assert_eq!(
+            fs::read_to_string(&config).unwrap(),
+            "{\n    \"modules-left\": [],\n    \"modules-right\": [\"clock\"],\n    \"battery#bat2\": {}\n}\n"
+        );
+
+        remove_config(&config);
+    }
+
+    #[test]
+    fn leaves_lists_with_comments_alone() {
+        let contents: &str = "\
+{
+    \"modules-left\": [
+        \"battery\" // laptop only
+    ],
+    \"modules-right\": [
+        \"clock\", /* \"backlight\", */
+    ],
+    \"battery\": {},
+    \"backlight\": {}
+}
+";
+        let config: PathBuf = write_config("comments", contents);
+
+        let removed: Vec<String> = remove_waybar_modules(&config, &["battery"]).unwrap();
+        assert!(removed.is_empty());
+
+        let added: Vec<String> = add_waybar_modules(&config, &["battery", "backlight"]).unwrap();
+        assert!(added.is_empty());
+
+        assert_eq!(fs::read_to_string(&config).unwrap(), contents);
+
+        remove_config(&config);
+    }
+}